@@ -1,9 +1,42 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{system_instruction, clock::Clock};
-use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
+use anchor_lang::system_program;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
 
 declare_id!("DVh3z1LQs6QXEtkc5TvzRq7v9fzoENc8UzeDedoiMAap");
 
+pub const MAX_NAME_LEN: usize = 64;
+pub const MAX_DESCRIPTION_LEN: usize = 256;
+pub const MAX_ELIGIBLE_ASSETS: usize = 16;
+pub const MAX_WHITELIST: usize = 16;
+pub const MAX_CHECKPOINTS: usize = 32;
+/// Caps on the rebalance rewards governance can set: REBAL base units per
+/// threshold's worth of deviation, and lamports reimbursed from the fee vault.
+pub const MAX_BASE_REWARD: u64 = 1_000_000_000_000;
+pub const MAX_LAMPORTS_REWARD: u64 = 10_000_000;
+/// One rule per `ProposalType`.
+pub const MAX_APPROVAL_RULES: usize = 17;
+pub const MAX_GUARDIANS: usize = 8;
+pub const MAX_GOVERNANCE_INSTRUCTIONS: usize = 8;
+/// Target weights are in basis points of the whole basket.
+pub const TOTAL_WEIGHT_BPS: u16 = 10_000;
+pub const SHARE_DECIMALS: u8 = 6;
+/// Oracle prices are quote units (this many decimals) per whole token; one
+/// share is worth one quote unit when a basket is first seeded.
+pub const PRICE_DECIMALS: u8 = 6;
+/// Prices older than this cannot be used for NAV.
+pub const MAX_PRICE_AGE_SECONDS: i64 = 300;
+pub const MAX_FEE_BPS: u16 = 1_000;
+/// Approval rule used for kinds without one: simple majority, no yes floor.
+pub const DEFAULT_APPROVAL_BPS: u16 = 5_000;
+/// Longest veREBAL lock; a lock this long votes with its full amount.
+pub const MAX_LOCK_SECONDS: i64 = 4 * 365 * 86_400;
+/// Lock ends are rounded down to a week so total supply decay can be tracked
+/// per week in `VotingSupply`.
+pub const LOCK_WEEK_SECONDS: i64 = 7 * 86_400;
+/// Ring size covering every week a live lock can end in.
+pub const SUPPLY_WEEKS: usize = (MAX_LOCK_SECONDS / LOCK_WEEK_SECONDS) as usize + 2;
+
 #[program]
 pub mod rebalancing_execution {
     use super::*;
@@ -15,15 +48,54 @@ pub mod rebalancing_execution {
         description: String,
         initial_threshold: u64,
         initial_strategy: u8,
-        initial_assets: Vec<Pubkey>,
+        initial_assets: Vec<AssetWeight>,
         quorum_percentage: u8,
         cooldown_seconds: u64,
         base_reward: u64,
         lamports_reward: u64,
         slash_factor: u64,
+        execution_delay_seconds: u64,
+        guardian: Pubkey,
+        guardians: Vec<Pubkey>,
+        guardian_threshold: u8,
+        approval_rules: Vec<ApprovalRule>,
+        proposal_deposit: u64,
+        min_proposer_balance: u64,
+        min_voting_period: u64,
+        max_voting_period: u64,
+        deposit_forfeit: DepositForfeit,
+        oracle_authority: Pubkey,
+        entry_fee_bps: u16,
+        exit_fee_bps: u16,
         mint_auth_bump: u8,
         fee_vault_bump: u8,
     ) -> Result<()> {
+        require!(initial_threshold > 0, ErrorCode::InvalidParameter);
+        require!(slash_factor >= 1, ErrorCode::InvalidParameter);
+        require!(base_reward <= MAX_BASE_REWARD, ErrorCode::InvalidParameter);
+        require!(lamports_reward <= MAX_LAMPORTS_REWARD, ErrorCode::InvalidParameter);
+        require!((1..=100).contains(&quorum_percentage), ErrorCode::InvalidParameter);
+        require!(cooldown_seconds <= i64::MAX as u64, ErrorCode::InvalidParameter);
+        require!(execution_delay_seconds <= i64::MAX as u64, ErrorCode::InvalidParameter);
+        require!(name.len() <= MAX_NAME_LEN, ErrorCode::InvalidParameter);
+        require!(description.len() <= MAX_DESCRIPTION_LEN, ErrorCode::InvalidParameter);
+        validate_weights(&initial_assets)?;
+        require!(min_voting_period <= max_voting_period, ErrorCode::InvalidParameter);
+        require!(max_voting_period <= i64::MAX as u64, ErrorCode::InvalidParameter);
+        validate_guardian_set(&guardians, guardian_threshold)?;
+        require!(
+            entry_fee_bps <= MAX_FEE_BPS && exit_fee_bps <= MAX_FEE_BPS,
+            ErrorCode::InvalidParameter
+        );
+        require!(approval_rules.len() <= MAX_APPROVAL_RULES, ErrorCode::InvalidParameter);
+        for (i, rule) in approval_rules.iter().enumerate() {
+            rule.validate()?;
+            require!(
+                approval_rules[..i].iter().all(|r| r.kind != rule.kind),
+                ErrorCode::InvalidParameter
+            );
+        }
+
         let cfg = &mut ctx.accounts.basket;
         cfg.initializer = ctx.accounts.authority.key();
         cfg.name = name;
@@ -31,7 +103,7 @@ pub mod rebalancing_execution {
         cfg.rebal_mint = ctx.accounts.rebal_mint.key();
         cfg.threshold = initial_threshold;
         cfg.strategy = initial_strategy;
-        cfg.eligible_assets = initial_assets;
+        cfg.eligible_assets = initial_assets.iter().map(|a| a.mint).collect();
         cfg.quorum_percentage = quorum_percentage;
         cfg.cooldown_seconds = cooldown_seconds;
         cfg.base_reward = base_reward;
@@ -41,272 +113,837 @@ pub mod rebalancing_execution {
         cfg.whitelist = Vec::new();
         cfg.mint_auth_bump = mint_auth_bump;
         cfg.fee_vault_bump = fee_vault_bump;
+        cfg.proposal_nonce = 0;
+        cfg.execution_delay_seconds = execution_delay_seconds;
+        cfg.guardian = guardian;
+        cfg.guardians = guardians;
+        cfg.guardian_threshold = guardian_threshold;
+        cfg.total_staked = 0;
+
+        let supply = &mut ctx.accounts.voting_supply;
+        supply.basket = cfg.key();
+        supply.ts = Clock::get()?.unix_timestamp;
+        supply.bias = 0;
+        supply.slope = 0;
+        supply.slope_changes = vec![0; SUPPLY_WEEKS];
+        cfg.approval_rules = approval_rules;
+        cfg.proposal_deposit = proposal_deposit;
+        cfg.min_proposer_balance = min_proposer_balance;
+        cfg.min_voting_period = min_voting_period;
+        cfg.max_voting_period = max_voting_period;
+        cfg.deposit_forfeit = deposit_forfeit;
+        cfg.treasury = ctx.accounts.treasury.key();
+        cfg.proposal_count = 0;
+        cfg.voting_mode = VotingMode::Linear;
+        cfg.oracle_authority = oracle_authority;
+        cfg.entry_fee_bps = entry_fee_bps;
+        cfg.exit_fee_bps = exit_fee_bps;
+
+        let composition = &mut ctx.accounts.composition;
+        composition.basket = cfg.key();
+        composition.assets = initial_assets;
         Ok(())
     }
 
-    /// Create a threshold‐change proposal (takes a supply snapshot & sets expiry).
-    pub fn propose_threshold(
-        ctx: Context<ProposeThreshold>,
-        new_threshold: u64,
-        expiration_ts: i64,
+    /// Open the caller's stake account for this basket.
+    pub fn create_stake_account(
+        ctx: Context<CreateStakeAccount>,
     ) -> Result<()> {
-        let cfg = &ctx.accounts.basket;
-        let p = &mut ctx.accounts.threshold_proposal;
-        p.proposer = ctx.accounts.proposer.key();
-        p.basket = cfg.key();
-        p.proposed_threshold = new_threshold;
-        p.yes_votes = 0;
-        p.no_votes = 0;
-        p.snapshot_supply = ctx.accounts.rebal_mint.supply;
-        p.quorum_percentage = cfg.quorum_percentage;
-        p.expiration = expiration_ts;
-        p.voters = Vec::new();
-        emit!(ProposalCreated {
-            basket: cfg.key(),
-            kind: ProposalType::Threshold,
-            proposer: p.proposer,
-            expiration: p.expiration,
-        });
+        let stake = &mut ctx.accounts.stake_account;
+        stake.owner = ctx.accounts.owner.key();
+        stake.basket = ctx.accounts.basket.key();
+        stake.amount = 0;
+        stake.lock_end = 0;
+        stake.active_votes = 0;
+        stake.checkpoints = Vec::new();
         Ok(())
     }
 
-    /// Vote on a threshold proposal.
-    pub fn vote_threshold(
-        ctx: Context<VoteThreshold>,
-        accept: bool,
+    /// Lock REBAL into the basket vault until `lock_end` (rounded down to a
+    /// week, at most `MAX_LOCK_SECONDS` out). Adding to a live lock keeps the later end.
+    pub fn lock_rebal(
+        ctx: Context<LockRebal>,
+        amount: u64,
+        lock_end: i64,
     ) -> Result<()> {
-        // 1) expiry check
         let clock = Clock::get()?;
-        let expiration = ctx.accounts.threshold_proposal.expiration;
-        require!(clock.unix_timestamp <= expiration, ErrorCode::ProposalExpired);
-
-        // 2) double‐voting check
-        let staker_key = ctx.accounts.staker.key();
-        let past_voters = &ctx.accounts.threshold_proposal.voters;
-        require!(!past_voters.contains(&staker_key), ErrorCode::AlreadyVoted);
-
-        // 3) determine weight
-        let weight = ctx.accounts.staker_tokens.amount;
+        let stake = &ctx.accounts.stake_account;
+        let (old_amount, old_end) = (stake.amount, stake.lock_end);
+        let lock_end = round_to_week(lock_end).max(stake.lock_end);
+        require!(
+            lock_end > clock.unix_timestamp
+                && lock_end <= clock.unix_timestamp.checked_add(MAX_LOCK_SECONDS).unwrap(),
+            ErrorCode::InvalidLockEnd
+        );
 
-        // 4) lock tokens into escrow
-        let cpi_ctx = ctx.accounts.into_transfer_to_escrow_context();
-        token::transfer(cpi_ctx, weight)?;
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner_tokens.to_account_info(),
+                    to: ctx.accounts.stake_vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
 
-        // 5) now mutably borrow the proposal
-        let p = &mut ctx.accounts.threshold_proposal;
-        if accept {
-            p.yes_votes = p.yes_votes.checked_add(weight).unwrap();
-        } else {
-            p.no_votes = p.no_votes.checked_add(weight).unwrap();
-        }
-        p.voters.push(staker_key);
+        let stake = &mut ctx.accounts.stake_account;
+        stake.amount = stake.amount.checked_add(amount).unwrap();
+        stake.lock_end = lock_end;
+        stake.checkpoint(clock.slot);
+        ctx.accounts.voting_supply.update_lock(
+            clock.unix_timestamp,
+            (old_amount, old_end),
+            (stake.amount, stake.lock_end),
+        );
+        let cfg = &mut ctx.accounts.basket;
+        cfg.total_staked = cfg.total_staked.checked_add(amount).unwrap();
+        emit!(StakeChanged {
+            basket: cfg.key(),
+            owner: stake.owner,
+            amount: stake.amount,
+            lock_end: stake.lock_end,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
 
-        emit!(Voted {
-            basket: p.basket,
-            kind: ProposalType::Threshold,
-            voter: staker_key,
-            weight,
-            accept,
+    /// Push an existing lock's end (rounded down to a week) further out,
+    /// restoring voting power.
+    pub fn extend_lock(
+        ctx: Context<ExtendLock>,
+        new_lock_end: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let new_lock_end = round_to_week(new_lock_end);
+        let stake = &mut ctx.accounts.stake_account;
+        require!(stake.amount > 0, ErrorCode::InsufficientStake);
+        require!(
+            new_lock_end > stake.lock_end
+                && new_lock_end > clock.unix_timestamp
+                && new_lock_end <= clock.unix_timestamp.checked_add(MAX_LOCK_SECONDS).unwrap(),
+            ErrorCode::InvalidLockEnd
+        );
+        let old_end = stake.lock_end;
+        stake.lock_end = new_lock_end;
+        stake.checkpoint(clock.slot);
+        ctx.accounts.voting_supply.update_lock(
+            clock.unix_timestamp,
+            (stake.amount, old_end),
+            (stake.amount, new_lock_end),
+        );
+        emit!(StakeChanged {
+            basket: ctx.accounts.basket.key(),
+            owner: stake.owner,
+            amount: stake.amount,
+            lock_end: stake.lock_end,
+            slot: clock.slot,
         });
         Ok(())
     }
 
-    /// Finalize threshold if quorum & majority met before expiry.
-    pub fn finalize_threshold(
-        ctx: Context<FinalizeThreshold>,
+    /// Withdraw the whole lock once it has expired and no votes hold it.
+    pub fn unlock_rebal(
+        ctx: Context<UnlockRebal>,
     ) -> Result<()> {
         let clock = Clock::get()?;
+        let stake = &ctx.accounts.stake_account;
+        require!(clock.unix_timestamp >= stake.lock_end, ErrorCode::LockActive);
+        require!(stake.active_votes == 0, ErrorCode::VotesOutstanding);
+        let amount = stake.amount;
+
+        let basket_key = ctx.accounts.basket.key();
+        let seeds = &[b"escrow_auth", basket_key.as_ref(), &[ctx.bumps.escrow_auth]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.stake_vault.to_account_info(),
+                    to: ctx.accounts.owner_tokens.to_account_info(),
+                    authority: ctx.accounts.escrow_auth.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            amount,
+        )?;
+
+        let stake = &mut ctx.accounts.stake_account;
+        stake.amount = 0;
+        stake.checkpoint(clock.slot);
         let cfg = &mut ctx.accounts.basket;
-        let p = &mut ctx.accounts.threshold_proposal;
+        cfg.total_staked = cfg.total_staked.checked_sub(amount).unwrap();
+        emit!(StakeChanged {
+            basket: basket_key,
+            owner: stake.owner,
+            amount: 0,
+            lock_end: stake.lock_end,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
 
-        require!(clock.unix_timestamp <= p.expiration, ErrorCode::ProposalExpired);
-        let total_votes = p.yes_votes.checked_add(p.no_votes).unwrap();
-        require!(
-            total_votes.checked_mul(100).unwrap()
-                >= p.snapshot_supply.checked_mul(p.quorum_percentage as u64).unwrap(),
-            ErrorCode::QuorumNotReached
-        );
-        require!(p.yes_votes > p.no_votes, ErrorCode::NotApproved);
+    /// Delegate voting power to `delegate` (`amount == 0` delegates the whole
+    /// lock). While delegated, the delegator can still vote the rest directly.
+    pub fn delegate_votes(
+        ctx: Context<DelegateVotes>,
+        delegate: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        require_keys_neq!(delegate, ctx.accounts.delegator.key(), ErrorCode::InvalidParameter);
+        let d = &mut ctx.accounts.delegation;
+        d.basket = ctx.accounts.basket.key();
+        d.delegator = ctx.accounts.delegator.key();
+        d.delegate = delegate;
+        d.amount = amount;
+        emit!(VotesDelegated {
+            basket: d.basket,
+            delegator: d.delegator,
+            delegate,
+            amount,
+        });
+        Ok(())
+    }
 
-        cfg.threshold = p.proposed_threshold;
-        emit!(ProposalFinalized {
-            basket: cfg.key(),
-            kind: ProposalType::Threshold,
-            approved: true,
+    /// Revoke a delegation, closing it back to the delegator.
+    pub fn undelegate_votes(
+        ctx: Context<UndelegateVotes>,
+    ) -> Result<()> {
+        let d = &ctx.accounts.delegation;
+        emit!(VotesUndelegated {
+            basket: d.basket,
+            delegator: d.delegator,
+            delegate: d.delegate,
         });
         Ok(())
     }
 
-    /// Create a strategy‐change proposal.
-    pub fn propose_strategy(
-        ctx: Context<ProposeStrategy>,
-        new_strategy: u8,
+    /// Create a governance proposal (snapshots total stake & the slot, sets expiry).
+    /// The proposer needs `min_proposer_balance` locked and escrows `proposal_deposit`.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        action: ProposalAction,
         expiration_ts: i64,
     ) -> Result<()> {
+        action.validate()?;
+        let clock = Clock::get()?;
         let cfg = &ctx.accounts.basket;
-        let p = &mut ctx.accounts.strategy_proposal;
+
+        // 1) spam protection: stake, voting period bounds, deposit
+        require!(
+            ctx.accounts.proposer_stake.amount >= cfg.min_proposer_balance,
+            ErrorCode::InsufficientStake
+        );
+        let period = expiration_ts
+            .checked_sub(clock.unix_timestamp)
+            .ok_or(ErrorCode::InvalidVotingPeriod)?;
+        require!(
+            period >= cfg.min_voting_period as i64 && period <= cfg.max_voting_period as i64,
+            ErrorCode::InvalidVotingPeriod
+        );
+        if cfg.proposal_deposit > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.proposer_tokens.to_account_info(),
+                        to: ctx.accounts.deposit_vault.to_account_info(),
+                        authority: ctx.accounts.proposer.to_account_info(),
+                    },
+                ),
+                cfg.proposal_deposit,
+            )?;
+        }
+
+        // 2) snapshot and open the proposal
+        let p = &mut ctx.accounts.proposal;
         p.proposer = ctx.accounts.proposer.key();
         p.basket = cfg.key();
-        p.proposed_strategy = new_strategy;
+        p.id = cfg.proposal_count;
+        p.action = action;
         p.yes_votes = 0;
         p.no_votes = 0;
-        p.snapshot_supply = ctx.accounts.rebal_mint.supply;
+        p.abstain_votes = 0;
+        p.participation = 0;
+        p.yes_power = 0;
+        p.voting_mode = cfg.voting_mode;
+        let supply = &mut ctx.accounts.voting_supply;
+        supply.advance(clock.unix_timestamp);
+        p.snapshot_supply = supply.power();
+        p.snapshot_slot = clock.slot;
+        p.snapshot_ts = clock.unix_timestamp;
         p.quorum_percentage = cfg.quorum_percentage;
+        let rule = cfg.approval_rule(p.action.kind());
+        p.approval_bps = rule.approval_bps;
+        p.min_yes_bps = rule.min_yes_bps;
         p.expiration = expiration_ts;
-        p.voters = Vec::new();
+        p.state = ProposalState::Active;
+        p.config_nonce = cfg.proposal_nonce;
+        p.eta = 0;
+        p.open_votes = 0;
+        p.deposit = cfg.proposal_deposit;
         emit!(ProposalCreated {
             basket: cfg.key(),
-            kind: ProposalType::Strategy,
+            proposal: p.key(),
+            id: p.id,
+            kind: p.action.kind(),
             proposer: p.proposer,
             expiration: p.expiration,
         });
+        let cfg = &mut ctx.accounts.basket;
+        cfg.proposal_count = cfg.proposal_count.checked_add(1).unwrap();
         Ok(())
     }
 
-    /// Vote on a strategy proposal.
-    pub fn vote_strategy(
-        ctx: Context<VoteStrategy>,
-        accept: bool,
+    /// Vote on a proposal with the veREBAL power held at its snapshot, less
+    /// any power delegated away; the stake stays locked until the vote is
+    /// released. Delegates add power delegated to them by passing
+    /// `[delegation, delegator_stake, delegated_record, direct_record]`
+    /// groups in `remaining_accounts`, where the records are the delegator's
+    /// PDAs (["delegated_vote", proposal, delegator]) and (["vote", proposal,
+    /// delegator]). A delegator's direct and delegated votes never add up
+    /// to more than its own power.
+    pub fn cast_vote<'info>(
+        ctx: Context<'_, '_, '_, 'info, CastVote<'info>>,
+        choice: VoteChoice,
     ) -> Result<()> {
+        // 1) state & expiry check (votes only count until expiration)
         let clock = Clock::get()?;
-        let expiration = ctx.accounts.strategy_proposal.expiration;
+        require!(
+            ctx.accounts.proposal.state == ProposalState::Active,
+            ErrorCode::ProposalNotActive
+        );
+        let expiration = ctx.accounts.proposal.expiration;
         require!(clock.unix_timestamp <= expiration, ErrorCode::ProposalExpired);
 
+        // 2) own power, less whatever is (or was already counted as) delegated
+        //    (double‐voting is rejected by the vote record `init`)
         let staker_key = ctx.accounts.staker.key();
-        let past_voters = &ctx.accounts.strategy_proposal.voters;
-        require!(!past_voters.contains(&staker_key), ErrorCode::AlreadyVoted);
-
-        let weight = ctx.accounts.staker_tokens.amount;
-        let cpi_ctx = ctx.accounts.into_transfer_to_escrow_context();
-        token::transfer(cpi_ctx, weight)?;
-
-        let p = &mut ctx.accounts.strategy_proposal;
-        if accept {
-            p.yes_votes = p.yes_votes.checked_add(weight).unwrap();
-        } else {
-            p.no_votes = p.no_votes.checked_add(weight).unwrap();
+        let basket_key = ctx.accounts.basket.key();
+        let proposal_key = ctx.accounts.proposal.key();
+        let (snapshot_slot, snapshot_ts) =
+            (ctx.accounts.proposal.snapshot_slot, ctx.accounts.proposal.snapshot_ts);
+        let mode = ctx.accounts.proposal.voting_mode;
+        let stake = &ctx.accounts.stake_account;
+        let mut delegated_away = 0u64;
+        if !ctx.accounts.delegation.data_is_empty() {
+            let d = Delegation::try_deserialize(&mut &ctx.accounts.delegation.try_borrow_data()?[..])?;
+            let cap = if d.amount == 0 { u64::MAX } else { d.amount };
+            delegated_away = stake.capped_power_at(snapshot_slot, snapshot_ts, cap);
+        }
+        if !ctx.accounts.delegated_record.data_is_empty() {
+            let d = VoteRecord::try_deserialize(&mut &ctx.accounts.delegated_record.try_borrow_data()?[..])?;
+            delegated_away = delegated_away.max(d.power);
+        }
+        let own_power = stake.power_at(snapshot_slot, snapshot_ts).saturating_sub(delegated_away);
+        let mut power = own_power;
+        let mut weight = mode.weight(own_power);
+        let mut records = 1u32;
+
+        // 3) delegated weight, locking each delegator's stake under its own record
+        require!(ctx.remaining_accounts.len().is_multiple_of(4), ErrorCode::BadDelegationAccounts);
+        for group in ctx.remaining_accounts.chunks(4) {
+            let (delegation_info, stake_info, record_info, direct_info) =
+                (&group[0], &group[1], &group[2], &group[3]);
+            require_keys_eq!(*delegation_info.owner, crate::ID, ErrorCode::BadDelegationAccounts);
+            require_keys_eq!(*stake_info.owner, crate::ID, ErrorCode::BadDelegationAccounts);
+            let delegation = Delegation::try_deserialize(&mut &delegation_info.try_borrow_data()?[..])?;
+            require_keys_eq!(delegation.delegate, staker_key, ErrorCode::BadDelegationAccounts);
+            let delegator = delegation.delegator;
+            let (expected_delegation, _) = Pubkey::find_program_address(
+                &[b"delegation", basket_key.as_ref(), delegator.as_ref()],
+                ctx.program_id,
+            );
+            let (expected_stake, _) = Pubkey::find_program_address(
+                &[b"stake", basket_key.as_ref(), delegator.as_ref()],
+                ctx.program_id,
+            );
+            let (expected_record, record_bump) = Pubkey::find_program_address(
+                &[b"delegated_vote", proposal_key.as_ref(), delegator.as_ref()],
+                ctx.program_id,
+            );
+            let (expected_direct, _) = Pubkey::find_program_address(
+                &[b"vote", proposal_key.as_ref(), delegator.as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(delegation_info.key(), expected_delegation, ErrorCode::BadDelegationAccounts);
+            require_keys_eq!(stake_info.key(), expected_stake, ErrorCode::BadDelegationAccounts);
+            require_keys_eq!(record_info.key(), expected_record, ErrorCode::BadDelegationAccounts);
+            require_keys_eq!(direct_info.key(), expected_direct, ErrorCode::BadDelegationAccounts);
+            require!(record_info.data_is_empty(), ErrorCode::AlreadyVoted);
+
+            // whatever the delegator already voted itself is no longer delegable
+            let mut stake = StakeAccount::try_deserialize(&mut &stake_info.try_borrow_data()?[..])?;
+            let cap = if delegation.amount == 0 { u64::MAX } else { delegation.amount };
+            let mut delegated_power = stake.capped_power_at(snapshot_slot, snapshot_ts, cap);
+            if !direct_info.data_is_empty() {
+                require_keys_eq!(*direct_info.owner, crate::ID, ErrorCode::BadDelegationAccounts);
+                let direct = VoteRecord::try_deserialize(&mut &direct_info.try_borrow_data()?[..])?;
+                let voted = direct.power.checked_sub(direct.delegated_power).unwrap();
+                delegated_power = delegated_power
+                    .min(stake.power_at(snapshot_slot, snapshot_ts).saturating_sub(voted));
+            }
+            if delegated_power == 0 {
+                continue;
+            }
+            let delegated = mode.weight(delegated_power);
+            stake.active_votes = stake.active_votes.checked_add(1).unwrap();
+            stake.try_serialize(&mut &mut stake_info.try_borrow_mut_data()?[..])?;
+
+            create_pda_account(
+                &ctx.accounts.staker.to_account_info(),
+                record_info,
+                &ctx.accounts.system_program.to_account_info(),
+                8 + VoteRecord::LEN,
+                &[&[b"delegated_vote", proposal_key.as_ref(), delegator.as_ref(), &[record_bump]]],
+            )?;
+            let record = VoteRecord {
+                proposal: proposal_key,
+                voter: delegator,
+                cast_by: staker_key,
+                weight: delegated,
+                power: delegated_power,
+                choice,
+                timestamp: clock.unix_timestamp,
+                delegators: 0,
+                delegated_power: 0,
+            };
+            record.try_serialize(&mut &mut record_info.try_borrow_mut_data()?[..])?;
+
+            weight = weight.checked_add(delegated).unwrap();
+            power = power.checked_add(delegated_power).unwrap();
+            records += 1;
         }
-        p.voters.push(staker_key);
+        require!(weight > 0, ErrorCode::NoVotingPower);
+
+        // 4) lock the stake until the vote is released
+        let stake = &mut ctx.accounts.stake_account;
+        stake.active_votes = stake.active_votes.checked_add(1).unwrap();
+
+        // 5) record the vote (weight includes delegated power)
+        let r = &mut ctx.accounts.vote_record;
+        r.proposal = proposal_key;
+        r.voter = staker_key;
+        r.cast_by = staker_key;
+        r.weight = weight;
+        r.power = power;
+        r.choice = choice;
+        r.timestamp = clock.unix_timestamp;
+        r.delegators = records - 1;
+        r.delegated_power = power - own_power;
+
+        // 6) now mutably borrow the proposal
+        let p = &mut ctx.accounts.proposal;
+        p.add_votes(choice, weight, power);
+        p.participation = p.participation.checked_add(power).unwrap();
+        p.open_votes = p.open_votes.checked_add(records).unwrap();
 
         emit!(Voted {
             basket: p.basket,
-            kind: ProposalType::Strategy,
+            kind: p.action.kind(),
             voter: staker_key,
             weight,
-            accept,
+            choice,
         });
         Ok(())
     }
 
-    /// Finalize strategy if quorum & majority met before expiry.
-    pub fn finalize_strategy(
-        ctx: Context<FinalizeStrategy>,
+    /// Switch an existing vote to another choice before expiry. A delegate
+    /// passes the vote record of every delegator it counted in
+    /// `remaining_accounts` so their records follow the new choice.
+    pub fn change_vote<'info>(
+        ctx: Context<'_, '_, '_, 'info, ChangeVote<'info>>,
+        choice: VoteChoice,
     ) -> Result<()> {
         let clock = Clock::get()?;
-        let cfg = &mut ctx.accounts.basket;
-        let p = &mut ctx.accounts.strategy_proposal;
+        let staker_key = ctx.accounts.staker.key();
+        let p = &mut ctx.accounts.proposal;
+        require!(p.state == ProposalState::Active, ErrorCode::ProposalNotActive);
+        require!(clock.unix_timestamp <= p.expiration, ErrorCode::ProposalExpired);
+
+        // 1) move the tally (its weight already includes any delegated power)
+        let r = &mut ctx.accounts.vote_record;
+        require_keys_eq!(r.cast_by, r.voter, ErrorCode::VotesDelegated);
+        p.remove_votes(r.choice, r.weight, r.power);
+        p.add_votes(choice, r.weight, r.power);
+
+        // 2) every delegator record counted by this vote follows it
+        require!(
+            ctx.remaining_accounts.len() == r.delegators as usize,
+            ErrorCode::BadDelegationAccounts
+        );
+        let proposal_key = p.key();
+        let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        for record_info in ctx.remaining_accounts.iter() {
+            require!(!seen.contains(record_info.key), ErrorCode::BadDelegationAccounts);
+            seen.push(record_info.key());
+            require_keys_eq!(*record_info.owner, crate::ID, ErrorCode::BadDelegationAccounts);
+            let mut record = VoteRecord::try_deserialize(&mut &record_info.try_borrow_data()?[..])?;
+            require_keys_eq!(record.proposal, proposal_key, ErrorCode::BadDelegationAccounts);
+            require_keys_eq!(record.cast_by, staker_key, ErrorCode::BadDelegationAccounts);
+            require!(record.voter != staker_key, ErrorCode::BadDelegationAccounts);
+            record.choice = choice;
+            record.timestamp = clock.unix_timestamp;
+            record.try_serialize(&mut &mut record_info.try_borrow_mut_data()?[..])?;
+        }
 
+        emit!(VoteChanged {
+            basket: p.basket,
+            proposal: p.key(),
+            voter: r.voter,
+            weight: r.weight,
+            previous: r.choice,
+            current: Some(choice),
+        });
+        r.choice = choice;
+        r.timestamp = clock.unix_timestamp;
+        Ok(())
+    }
+
+    /// Withdraw a vote before expiry, releasing the stake lock and closing the
+    /// vote record (so the voter may vote again). Delegates must also release
+    /// every delegator they counted by passing `[vote_record, stake_account,
+    /// staker]` triples in `remaining_accounts`; the delegators' record rent
+    /// returns to the delegate that paid it.
+    pub fn retract_vote<'info>(
+        ctx: Context<'_, '_, '_, 'info, RetractVote<'info>>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let staker_key = ctx.accounts.staker.key();
+        let basket_key = ctx.accounts.basket.key();
+        let p = &mut ctx.accounts.proposal;
+        require!(p.state == ProposalState::Active, ErrorCode::ProposalNotActive);
         require!(clock.unix_timestamp <= p.expiration, ErrorCode::ProposalExpired);
-        let total_votes = p.yes_votes.checked_add(p.no_votes).unwrap();
+
+        // 1) pull the vote (its weight already includes any delegated power)
+        let r = &ctx.accounts.vote_record;
+        require_keys_eq!(r.cast_by, r.voter, ErrorCode::VotesDelegated);
+        p.remove_votes(r.choice, r.weight, r.power);
+        p.participation = p.participation.checked_sub(r.power).unwrap();
+        p.open_votes = p.open_votes.checked_sub(1).unwrap();
+        let stake = &mut ctx.accounts.stake_account;
+        stake.active_votes = stake.active_votes.checked_sub(1).unwrap();
+
+        // 2) release every delegator counted by this vote (closed records
+        //    can't be passed twice)
         require!(
-            total_votes.checked_mul(100).unwrap()
-                >= p.snapshot_supply.checked_mul(p.quorum_percentage as u64).unwrap(),
-            ErrorCode::QuorumNotReached
+            ctx.remaining_accounts.len() == 3 * r.delegators as usize,
+            ErrorCode::BadRefundAccounts
         );
-        require!(p.yes_votes > p.no_votes, ErrorCode::NotApproved);
+        let proposal_key = p.key();
+        for group in ctx.remaining_accounts.chunks(3) {
+            let (record_info, stake_info, rent_to) = (&group[0], &group[1], &group[2]);
+            require_keys_eq!(rent_to.key(), staker_key, ErrorCode::BadRefundAccounts);
+            let record = release_vote_record(record_info, stake_info, rent_to, &basket_key, &proposal_key)?;
+            require!(record.voter != staker_key, ErrorCode::BadRefundAccounts);
+            p.open_votes = p.open_votes.checked_sub(1).unwrap();
+        }
 
-        cfg.strategy = p.proposed_strategy;
-        emit!(ProposalFinalized {
-            basket: cfg.key(),
-            kind: ProposalType::Strategy,
-            approved: true,
+        emit!(VoteChanged {
+            basket: basket_key,
+            proposal: proposal_key,
+            voter: staker_key,
+            weight: r.weight,
+            previous: r.choice,
+            current: None,
         });
         Ok(())
     }
 
-    /// Create an assets‐change proposal.
-    pub fn propose_assets(
-        ctx: Context<ProposeAssets>,
-        new_assets: Vec<Pubkey>,
-        expiration_ts: i64,
+    /// Settle a proposal once voting has closed. Anyone may call this:
+    /// passing proposals are queued behind the timelock, the rest are marked Defeated/Expired.
+    /// The deposit is refunded if quorum was reached and forfeited otherwise.
+    pub fn finalize_proposal(
+        ctx: Context<FinalizeProposal>,
     ) -> Result<()> {
-        let cfg = &ctx.accounts.basket;
-        let p = &mut ctx.accounts.assets_proposal;
-        p.proposer = ctx.accounts.proposer.key();
-        p.basket = cfg.key();
-        p.proposed_assets = new_assets;
-        p.yes_votes = 0;
-        p.no_votes = 0;
-        p.snapshot_supply = ctx.accounts.rebal_mint.supply;
-        p.quorum_percentage = cfg.quorum_percentage;
-        p.expiration = expiration_ts;
-        p.voters = Vec::new();
-        emit!(ProposalCreated {
+        let clock = Clock::get()?;
+        let cfg = &mut ctx.accounts.basket;
+        let p = &mut ctx.accounts.proposal;
+
+        require!(p.state != ProposalState::Executed, ErrorCode::ProposalAlreadyExecuted);
+        require!(p.state == ProposalState::Active, ErrorCode::ProposalNotActive);
+        require!(clock.unix_timestamp > p.expiration, ErrorCode::ProposalStillActive);
+
+        // quorum counts raw power of every vote, abstentions included, in either mode
+        let quorum_reached = p.participation.checked_mul(100).unwrap()
+            >= p.snapshot_supply.checked_mul(p.quorum_percentage as u64).unwrap();
+        let approved = quorum_reached && p.meets_approval_rule();
+        p.state = if !quorum_reached {
+            ProposalState::Expired
+        } else if approved {
+            ProposalState::Queued
+        } else {
+            ProposalState::Defeated
+        };
+
+        // settle the deposit
+        if p.deposit > 0 {
+            let basket_key = cfg.key();
+            let seeds = &[b"escrow_auth", basket_key.as_ref(), &[ctx.bumps.escrow_auth]];
+            let refunded = quorum_reached;
+            if refunded {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.deposit_vault.to_account_info(),
+                            to: ctx.accounts.proposer_tokens.to_account_info(),
+                            authority: ctx.accounts.escrow_auth.to_account_info(),
+                        },
+                        &[&seeds[..]],
+                    ),
+                    p.deposit,
+                )?;
+            } else {
+                let sink = match cfg.deposit_forfeit {
+                    DepositForfeit::Treasury => ctx.accounts.treasury.to_account_info(),
+                    DepositForfeit::Burn => ctx.accounts.rebal_mint.to_account_info(),
+                };
+                forfeit_deposit(
+                    cfg.deposit_forfeit,
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.deposit_vault.to_account_info(),
+                    &ctx.accounts.escrow_auth,
+                    &sink,
+                    &[&seeds[..]],
+                    p.deposit,
+                )?;
+            }
+            emit!(DepositReleased {
+                basket: basket_key,
+                proposal: p.key(),
+                amount: p.deposit,
+                refunded,
+            });
+            p.deposit = 0;
+        }
+
+        emit!(ProposalFinalized {
             basket: cfg.key(),
-            kind: ProposalType::Assets,
-            proposer: p.proposer,
-            expiration: p.expiration,
+            kind: p.action.kind(),
+            approved,
         });
+        if approved {
+            p.eta = clock
+                .unix_timestamp
+                .checked_add(cfg.execution_delay_seconds as i64)
+                .unwrap();
+            emit!(ProposalQueued {
+                basket: cfg.key(),
+                proposal: p.key(),
+                eta: p.eta,
+            });
+        }
         Ok(())
     }
 
-    /// Vote on an assets proposal.
-    pub fn vote_assets(
-        ctx: Context<VoteAssets>,
-        accept: bool,
+    /// Apply a queued proposal once its timelock has elapsed. `Execute` proposals
+    /// need every account their instructions touch (programs included) in
+    /// `remaining_accounts`; they are invoked signed by the governance PDA.
+    /// `Assets` proposals pass the asset vaults `check_listing_vaults` expects.
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>,
     ) -> Result<()> {
         let clock = Clock::get()?;
-        let expiration = ctx.accounts.assets_proposal.expiration;
-        require!(clock.unix_timestamp <= expiration, ErrorCode::ProposalExpired);
-
-        let staker_key = ctx.accounts.staker.key();
-        let past_voters = &ctx.accounts.assets_proposal.voters;
-        require!(!past_voters.contains(&staker_key), ErrorCode::AlreadyVoted);
+        let cfg = &mut ctx.accounts.basket;
+        let p = &mut ctx.accounts.proposal;
 
-        let weight = ctx.accounts.staker_tokens.amount;
-        let cpi_ctx = ctx.accounts.into_transfer_to_escrow_context();
-        token::transfer(cpi_ctx, weight)?;
+        require!(p.state != ProposalState::Executed, ErrorCode::ProposalAlreadyExecuted);
+        require!(p.state == ProposalState::Queued, ErrorCode::ProposalNotQueued);
+        require!(clock.unix_timestamp >= p.eta, ErrorCode::TimelockActive);
+        // only proposals opened against the current config version may apply
+        require!(p.config_nonce == cfg.proposal_nonce, ErrorCode::StaleProposal);
 
-        let p = &mut ctx.accounts.assets_proposal;
-        if accept {
-            p.yes_votes = p.yes_votes.checked_add(weight).unwrap();
-        } else {
-            p.no_votes = p.no_votes.checked_add(weight).unwrap();
+        if let ProposalAction::Assets(assets) = &p.action {
+            check_listing_vaults(ctx.remaining_accounts, &cfg.key(), assets)?;
         }
-        p.voters.push(staker_key);
+        p.action.apply(cfg, &mut ctx.accounts.composition)?;
+        if let ProposalAction::Execute(instructions) = &p.action {
+            let basket_key = cfg.key();
+            let seeds: &[&[u8]] = &[b"governance", basket_key.as_ref(), &[ctx.bumps.governance]];
+            let mut infos = ctx.remaining_accounts.to_vec();
+            infos.push(ctx.accounts.governance.to_account_info());
+            for gix in instructions {
+                anchor_lang::solana_program::program::invoke_signed(&gix.to_instruction(), &infos, &[seeds])?;
+            }
+        }
+        cfg.proposal_nonce = cfg.proposal_nonce.checked_add(1).unwrap();
+        p.state = ProposalState::Executed;
+        emit!(ProposalExecuted {
+            basket: cfg.key(),
+            proposal: p.key(),
+            kind: p.action.kind(),
+        });
+        Ok(())
+    }
 
-        emit!(Voted {
-            basket: p.basket,
-            kind: ProposalType::Assets,
-            voter: staker_key,
-            weight,
-            accept,
+    /// Admin fast‐path for whitelist edits (governance uses `ProposalAction::WhitelistChange`).
+    pub fn update_whitelist(
+        ctx: Context<UpdateWhitelist>,
+        add: Vec<Pubkey>,
+        remove: Vec<Pubkey>,
+    ) -> Result<()> {
+        apply_whitelist_change(&mut ctx.accounts.basket, &add, &remove)
+    }
+
+    /// Release a vote's lock on the voter's stake once voting on the proposal
+    /// has closed. Closes the vote record, rent back to whoever cast it.
+    pub fn withdraw_vote_escrow(
+        ctx: Context<WithdrawVoteEscrow>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let p = &mut ctx.accounts.proposal;
+        require!(
+            p.state != ProposalState::Active || clock.unix_timestamp > p.expiration,
+            ErrorCode::ProposalStillActive
+        );
+        p.open_votes = p.open_votes.checked_sub(1).unwrap();
+
+        let stake = &mut ctx.accounts.stake_account;
+        stake.active_votes = stake.active_votes.checked_sub(1).unwrap();
+
+        emit!(VoteEscrowWithdrawn {
+            basket: ctx.accounts.basket.key(),
+            proposal: p.key(),
+            voter: ctx.accounts.staker.key(),
+            power: ctx.accounts.vote_record.power,
         });
         Ok(())
     }
 
-    /// Finalize assets if quorum & majority met before expiry.
-    pub fn finalize_assets(
-        ctx: Context<FinalizeAssets>,
+    /// Cancel a proposal: the proposer while voting is open and before anyone
+    /// votes, the basket guardian while voting is open or once the proposal
+    /// has passed, until it executes. Vote locks are released from
+    /// `remaining_accounts` as `[vote_record, stake_account, cast_by]` triples
+    /// (repeat the call to sweep in batches); the proposal closes to the
+    /// proposer once every vote is released.
+    pub fn cancel_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelProposal<'info>>,
     ) -> Result<()> {
         let clock = Clock::get()?;
-        let cfg = &mut ctx.accounts.basket;
-        let p = &mut ctx.accounts.assets_proposal;
+        let signer = ctx.accounts.authority.key();
+        let guardian = ctx.accounts.basket.guardian;
+        let forfeit = ctx.accounts.basket.deposit_forfeit;
+        let basket_key = ctx.accounts.basket.key();
+        let p = &mut ctx.accounts.proposal;
+        let open = clock.unix_timestamp <= p.expiration;
+
+        // 1) authorization (an already‐cancelled proposal can be swept by anyone);
+        //    only a proposer withdrawing an untouched proposal gets its deposit back
+        let refund = match p.state {
+            ProposalState::Cancelled => false,
+            ProposalState::Executed => return err!(ErrorCode::ProposalAlreadyExecuted),
+            ProposalState::Active if open && signer == p.proposer && p.open_votes == 0 => true,
+            ProposalState::Active if open && signer == guardian => false,
+            ProposalState::Queued if signer == guardian => false,
+            _ => return err!(ErrorCode::CancelNotAllowed),
+        };
+        if p.state != ProposalState::Cancelled {
+            p.state = ProposalState::Cancelled;
+            emit!(ProposalCancelled {
+                basket: p.basket,
+                proposal: p.key(),
+                cancelled_by: signer,
+            });
+        }
 
-        require!(clock.unix_timestamp <= p.expiration, ErrorCode::ProposalExpired);
-        let total_votes = p.yes_votes.checked_add(p.no_votes).unwrap();
+        // 2) settle a deposit still held (queued proposals settled theirs at finalize)
+        if p.deposit > 0 {
+            let seeds = &[b"escrow_auth", basket_key.as_ref(), &[ctx.bumps.escrow_auth]];
+            if refund {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.deposit_vault.to_account_info(),
+                            to: ctx.accounts.proposer_tokens.to_account_info(),
+                            authority: ctx.accounts.escrow_auth.to_account_info(),
+                        },
+                        &[&seeds[..]],
+                    ),
+                    p.deposit,
+                )?;
+            } else {
+                let sink = match forfeit {
+                    DepositForfeit::Treasury => ctx.accounts.treasury.to_account_info(),
+                    DepositForfeit::Burn => ctx.accounts.rebal_mint.to_account_info(),
+                };
+                forfeit_deposit(
+                    forfeit,
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.deposit_vault.to_account_info(),
+                    &ctx.accounts.escrow_auth,
+                    &sink,
+                    &[&seeds[..]],
+                    p.deposit,
+                )?;
+            }
+            emit!(DepositReleased {
+                basket: basket_key,
+                proposal: p.key(),
+                amount: p.deposit,
+                refunded: refund,
+            });
+            p.deposit = 0;
+        }
+
+        // 3) release vote locks passed in remaining_accounts
+        require!(ctx.remaining_accounts.len().is_multiple_of(3), ErrorCode::BadRefundAccounts);
+        let proposal_key = p.key();
+        for group in ctx.remaining_accounts.chunks(3) {
+            let (record_info, stake_info, rent_to) = (&group[0], &group[1], &group[2]);
+            let record = release_vote_record(record_info, stake_info, rent_to, &basket_key, &proposal_key)?;
+            p.open_votes = p.open_votes.checked_sub(1).unwrap();
+            emit!(VoteEscrowWithdrawn {
+                basket: basket_key,
+                proposal: proposal_key,
+                voter: record.voter,
+                power: record.power,
+            });
+        }
+
+        // 4) close once no vote still holds a lock
+        if p.open_votes == 0 {
+            p.close(ctx.accounts.proposer.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    /// Veto a queued proposal with `guardian_threshold` signatures from the
+    /// guardian council: `guardian` plus any co‐signing guardians passed in
+    /// `remaining_accounts`.
+    pub fn veto_proposal(
+        ctx: Context<VetoProposal>,
+    ) -> Result<()> {
+        let cfg = &ctx.accounts.basket;
+        let p = &mut ctx.accounts.proposal;
+        require!(p.state == ProposalState::Queued, ErrorCode::ProposalNotQueued);
+
+        // 1) count distinct council members that signed
+        let mut signers: Vec<Pubkey> = ctx
+            .remaining_accounts
+            .iter()
+            .filter(|a| a.is_signer)
+            .map(|a| a.key())
+            .collect();
+        signers.push(ctx.accounts.guardian.key());
+        let approvals: Vec<Pubkey> = cfg
+            .guardians
+            .iter()
+            .filter(|g| signers.contains(g))
+            .cloned()
+            .collect();
         require!(
-            total_votes.checked_mul(100).unwrap()
-                >= p.snapshot_supply.checked_mul(p.quorum_percentage as u64).unwrap(),
-            ErrorCode::QuorumNotReached
+            cfg.guardian_threshold > 0 && approvals.len() >= cfg.guardian_threshold as usize,
+            ErrorCode::NotEnoughGuardians
         );
-        require!(p.yes_votes > p.no_votes, ErrorCode::NotApproved);
 
-        cfg.eligible_assets = p.proposed_assets.clone();
-        emit!(ProposalFinalized {
+        // 2) votes stay locked until withdrawn as with any closed proposal
+        p.state = ProposalState::Vetoed;
+        emit!(ProposalVetoed {
             basket: cfg.key(),
-            kind: ProposalType::Assets,
-            approved: true,
+            proposal: p.key(),
+            guardians: approvals,
         });
         Ok(())
     }
@@ -394,6 +1031,379 @@ pub mod rebalancing_execution {
 
         Ok(())
     }
+
+    /// Open the custody vault (and its price feed) for an eligible asset, or
+    /// for one an open `Assets` proposal (passed as `proposal`) would list.
+    /// Permissionless, so vaults exist by the time the listing executes.
+    pub fn create_asset_vault(
+        ctx: Context<CreateAssetVault>,
+    ) -> Result<()> {
+        let cfg = &ctx.accounts.basket;
+        let mint = ctx.accounts.mint.key();
+        let proposed = match ctx.accounts.proposal.as_ref().map(|p| (p.state, &p.action)) {
+            Some((ProposalState::Active | ProposalState::Queued, ProposalAction::Assets(assets))) => {
+                assets.iter().any(|a| a.mint == mint)
+            }
+            _ => false,
+        };
+        require!(cfg.eligible_assets.contains(&mint) || proposed, ErrorCode::AssetNotEligible);
+        let feed = &mut ctx.accounts.price_feed;
+        feed.basket = cfg.key();
+        feed.mint = mint;
+        feed.decimals = ctx.accounts.mint.decimals;
+        feed.price = 0;
+        feed.updated_ts = 0;
+        emit!(AssetVaultCreated {
+            basket: cfg.key(),
+            mint,
+            vault: ctx.accounts.vault.key(),
+        });
+        Ok(())
+    }
+
+    /// Move the whole balance of a delisted asset's vault, or of any vault
+    /// once no shares are outstanding, to a token account of the governance
+    /// PDA (["governance", basket]), where `Execute` proposals can dispose of
+    /// it. Permissionless.
+    pub fn sweep_asset_vault(
+        ctx: Context<SweepAssetVault>,
+    ) -> Result<()> {
+        let cfg = &ctx.accounts.basket;
+        let mint = ctx.accounts.mint.key();
+        require!(
+            !cfg.eligible_assets.contains(&mint) || ctx.accounts.share_mint.supply == 0,
+            ErrorCode::AssetStillListed
+        );
+        let amount = ctx.accounts.vault.amount;
+        if amount > 0 {
+            let basket_key = cfg.key();
+            let seeds = &[b"basket_auth", basket_key.as_ref(), &[ctx.bumps.basket_auth]];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.vault.to_account_info(),
+                        to: ctx.accounts.destination.to_account_info(),
+                        authority: ctx.accounts.basket_auth.to_account_info(),
+                    },
+                    &[&seeds[..]],
+                ),
+                amount,
+            )?;
+        }
+        emit!(AssetVaultSwept {
+            basket: cfg.key(),
+            mint,
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Mint `shares` against a pro‐rata slice of every eligible asset, rounded
+    /// up in the vaults' favor. `remaining_accounts` holds `[vault, owner_tokens]`
+    /// pairs in `eligible_assets` order; `max_amounts` caps each transfer. The
+    /// first deposit (no shares outstanding) sets the ratio with `max_amounts`
+    /// and needs every vault empty.
+    pub fn deposit_in_kind<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositInKind<'info>>,
+        shares: u64,
+        max_amounts: Vec<u64>,
+    ) -> Result<()> {
+        let cfg = &ctx.accounts.basket;
+        let basket_key = cfg.key();
+        let assets = &cfg.eligible_assets;
+        require!(shares > 0 && !assets.is_empty(), ErrorCode::InvalidParameter);
+        require!(max_amounts.len() == assets.len(), ErrorCode::InvalidParameter);
+        require!(ctx.remaining_accounts.len() == 2 * assets.len(), ErrorCode::BadVaultAccounts);
+        let supply = ctx.accounts.share_mint.supply;
+
+        // 1) pull each asset into its vault
+        let mut amounts = Vec::with_capacity(assets.len());
+        for ((mint, pair), max_amount) in assets
+            .iter()
+            .zip(ctx.remaining_accounts.chunks(2))
+            .zip(max_amounts.iter())
+        {
+            let (vault_info, owner_tokens) = (&pair[0], &pair[1]);
+            let vault = load_asset_vault(vault_info, &basket_key, mint)?;
+            let amount = if supply == 0 {
+                // leftovers would go to the seeder; sweep them first
+                require!(vault.amount == 0, ErrorCode::VaultNotEmpty);
+                require!(*max_amount > 0, ErrorCode::InvalidParameter);
+                *max_amount
+            } else {
+                mul_div(shares, vault.amount, supply, true)
+            };
+            require!(amount <= *max_amount, ErrorCode::SlippageExceeded);
+            if amount > 0 {
+                token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: owner_tokens.clone(),
+                            to: vault_info.clone(),
+                            authority: ctx.accounts.owner.to_account_info(),
+                        },
+                    ),
+                    amount,
+                )?;
+            }
+            amounts.push(amount);
+        }
+        require!(amounts.iter().any(|a| *a > 0), ErrorCode::InvalidParameter);
+
+        // 2) mint the shares
+        let seeds = &[b"basket_auth", basket_key.as_ref(), &[ctx.bumps.basket_auth]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.owner_shares.to_account_info(),
+                    authority: ctx.accounts.basket_auth.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            shares,
+        )?;
+        emit!(SharesMinted {
+            basket: basket_key,
+            owner: ctx.accounts.owner.key(),
+            shares,
+            amounts,
+            fee: 0,
+        });
+        Ok(())
+    }
+
+    /// Burn `shares` for a pro‐rata slice of every eligible asset, rounded down
+    /// in the vaults' favor. Accounts as in `deposit_in_kind`; `min_amounts`
+    /// floors each withdrawal.
+    pub fn redeem_in_kind<'info>(
+        ctx: Context<'_, '_, '_, 'info, RedeemInKind<'info>>,
+        shares: u64,
+        min_amounts: Vec<u64>,
+    ) -> Result<()> {
+        let cfg = &ctx.accounts.basket;
+        let basket_key = cfg.key();
+        let assets = &cfg.eligible_assets;
+        require!(shares > 0, ErrorCode::InvalidParameter);
+        require!(min_amounts.len() == assets.len(), ErrorCode::InvalidParameter);
+        require!(ctx.remaining_accounts.len() == 2 * assets.len(), ErrorCode::BadVaultAccounts);
+        let supply = ctx.accounts.share_mint.supply;
+
+        // 1) burn first so the payout is priced on the pre‐burn supply
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.owner_shares.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+
+        // 2) pay out each asset
+        let seeds = &[b"basket_auth", basket_key.as_ref(), &[ctx.bumps.basket_auth]];
+        let mut amounts = Vec::with_capacity(assets.len());
+        for ((mint, pair), min_amount) in assets
+            .iter()
+            .zip(ctx.remaining_accounts.chunks(2))
+            .zip(min_amounts.iter())
+        {
+            let (vault_info, owner_tokens) = (&pair[0], &pair[1]);
+            let vault = load_asset_vault(vault_info, &basket_key, mint)?;
+            let amount = mul_div(shares, vault.amount, supply, false);
+            require!(amount >= *min_amount, ErrorCode::SlippageExceeded);
+            if amount > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: vault_info.clone(),
+                            to: owner_tokens.clone(),
+                            authority: ctx.accounts.basket_auth.to_account_info(),
+                        },
+                        &[&seeds[..]],
+                    ),
+                    amount,
+                )?;
+            }
+            amounts.push(amount);
+        }
+        emit!(SharesBurned {
+            basket: basket_key,
+            owner: ctx.accounts.owner.key(),
+            shares,
+            amounts,
+            fee: 0,
+        });
+        Ok(())
+    }
+
+    /// Oracle authority posts the price of one whole token in quote units.
+    pub fn update_price(
+        ctx: Context<UpdatePrice>,
+        price: u64,
+    ) -> Result<()> {
+        require!(price > 0, ErrorCode::InvalidParameter);
+        let clock = Clock::get()?;
+        let feed = &mut ctx.accounts.price_feed;
+        feed.price = price;
+        feed.updated_ts = clock.unix_timestamp;
+        emit!(PriceUpdated {
+            basket: feed.basket,
+            mint: feed.mint,
+            price,
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Deposit `amount` of one eligible asset for shares priced at oracle NAV,
+    /// less `entry_fee_bps` (left in the basket). `remaining_accounts` holds
+    /// `[vault, price_feed]` pairs in `eligible_assets` order. Rejected if it
+    /// would push an asset's weight further than `threshold` bps from target.
+    pub fn deposit_single<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositSingle<'info>>,
+        amount: u64,
+        min_shares: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let cfg = &ctx.accounts.basket;
+        let basket_key = cfg.key();
+        let supply = ctx.accounts.share_mint.supply;
+        require!(amount > 0, ErrorCode::InvalidParameter);
+        require!(supply > 0, ErrorCode::BasketNotSeeded);
+
+        // 1) price the basket and the deposit
+        let holdings = load_holdings(ctx.remaining_accounts, &basket_key, &cfg.eligible_assets, clock.unix_timestamp)?;
+        let index = asset_index(cfg, &ctx.accounts.mint.key())?;
+        let before = holdings.iter().map(|h| h.value(h.amount)).collect::<Result<Vec<u128>>>()?;
+        let nav = checked_sum(&before)?;
+        require!(nav > 0, ErrorCode::BasketNotSeeded);
+        let value_in = holdings[index].value(amount)?;
+        let fee = value_in.checked_mul(cfg.entry_fee_bps as u128).ok_or(ErrorCode::InvalidParameter)? / 10_000;
+        let shares = (value_in - fee)
+            .checked_mul(supply as u128)
+            .and_then(|v| v.checked_div(nav))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(ErrorCode::InvalidParameter)?;
+        require!(shares > 0 && shares >= min_shares, ErrorCode::SlippageExceeded);
+
+        // 2) weight guard
+        let mut after = before.clone();
+        after[index] = after[index].checked_add(value_in).ok_or(ErrorCode::InvalidParameter)?;
+        check_weight_deviation(&before, &after, &cfg.eligible_assets, &ctx.accounts.composition, cfg.threshold)?;
+
+        // 3) move the asset in and mint
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner_tokens.to_account_info(),
+                    to: ctx.remaining_accounts[2 * index].clone(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        let seeds = &[b"basket_auth", basket_key.as_ref(), &[ctx.bumps.basket_auth]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.owner_shares.to_account_info(),
+                    authority: ctx.accounts.basket_auth.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            shares,
+        )?;
+
+        let mut amounts = vec![0; holdings.len()];
+        amounts[index] = amount;
+        emit!(SharesMinted {
+            basket: basket_key,
+            owner: ctx.accounts.owner.key(),
+            shares,
+            amounts,
+            fee: u64::try_from(fee).unwrap(),
+        });
+        Ok(())
+    }
+
+    /// Burn `shares` for one eligible asset at oracle NAV, less `exit_fee_bps`
+    /// (left in the basket). Accounts and weight guard as in `deposit_single`.
+    pub fn redeem_single<'info>(
+        ctx: Context<'_, '_, '_, 'info, RedeemSingle<'info>>,
+        shares: u64,
+        min_amount: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let cfg = &ctx.accounts.basket;
+        let basket_key = cfg.key();
+        let supply = ctx.accounts.share_mint.supply;
+        require!(shares > 0 && shares <= supply, ErrorCode::InvalidParameter);
+
+        // 1) price the shares and convert to the asset, rounding down
+        let holdings = load_holdings(ctx.remaining_accounts, &basket_key, &cfg.eligible_assets, clock.unix_timestamp)?;
+        let index = asset_index(cfg, &ctx.accounts.mint.key())?;
+        let before = holdings.iter().map(|h| h.value(h.amount)).collect::<Result<Vec<u128>>>()?;
+        let nav = checked_sum(&before)?;
+        let value_out = (shares as u128).checked_mul(nav).ok_or(ErrorCode::InvalidParameter)? / supply as u128;
+        let fee = value_out.checked_mul(cfg.exit_fee_bps as u128).ok_or(ErrorCode::InvalidParameter)? / 10_000;
+        let amount = holdings[index].amount_for(value_out - fee)?;
+        require!(amount <= holdings[index].amount, ErrorCode::InsufficientLiquidity);
+        require!(amount > 0 && amount >= min_amount, ErrorCode::SlippageExceeded);
+
+        // 2) weight guard (skipped when the basket is fully redeemed)
+        let mut after = before.clone();
+        after[index] -= holdings[index].value(amount)?;
+        if shares < supply {
+            check_weight_deviation(&before, &after, &cfg.eligible_assets, &ctx.accounts.composition, cfg.threshold)?;
+        }
+
+        // 3) burn and pay out
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.owner_shares.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+        let seeds = &[b"basket_auth", basket_key.as_ref(), &[ctx.bumps.basket_auth]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.remaining_accounts[2 * index].clone(),
+                    to: ctx.accounts.owner_tokens.to_account_info(),
+                    authority: ctx.accounts.basket_auth.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            amount,
+        )?;
+
+        let mut amounts = vec![0; holdings.len()];
+        amounts[index] = amount;
+        emit!(SharesBurned {
+            basket: basket_key,
+            owner: ctx.accounts.owner.key(),
+            shares,
+            amounts,
+            fee: u64::try_from(fee).unwrap(),
+        });
+        Ok(())
+    }
 }
 
 // ─── Accounts ─────────────────────────────────────────────────────────────
@@ -416,45 +1426,988 @@ pub struct BasketConfig {
     pub whitelist: Vec<Pubkey>,
     pub mint_auth_bump: u8,
     pub fee_vault_bump: u8,
+    /// Config version; bumped whenever a proposal is executed.
+    pub proposal_nonce: u64,
+    /// Timelock between a proposal passing and `execute_proposal`.
+    pub execution_delay_seconds: u64,
+    /// May cancel any proposal before it executes. Changed only by governance.
+    pub guardian: Pubkey,
+    /// Emergency council; `guardian_threshold` of them can veto queued
+    /// proposals (0 disables the council). Changed only by governance.
+    pub guardians: Vec<Pubkey>,
+    pub guardian_threshold: u8,
+    /// Raw REBAL locked across all stake accounts (expired locks included).
+    pub total_staked: u64,
+    /// Per‐kind approval rules; kinds without one pass on a simple majority.
+    pub approval_rules: Vec<ApprovalRule>,
+    /// REBAL escrowed by each new proposal.
+    pub proposal_deposit: u64,
+    /// Locked REBAL a proposer must hold.
+    pub min_proposer_balance: u64,
+    /// Bounds on `expiration - now` at proposal creation.
+    pub min_voting_period: u64,
+    pub max_voting_period: u64,
+    /// What happens to deposits of proposals that miss quorum.
+    pub deposit_forfeit: DepositForfeit,
+    /// REBAL token account receiving forfeited deposits under `Treasury`.
+    pub treasury: Pubkey,
+    /// Proposals created so far; the next proposal's id.
+    pub proposal_count: u64,
+    /// How veREBAL power turns into vote weight; changed only by governance.
+    pub voting_mode: VotingMode,
+    /// Posts asset prices used for single‐asset deposits and redemptions.
+    /// Changed only by governance.
+    pub oracle_authority: Pubkey,
+    /// Share of value kept by the basket on single‐asset deposits / redemptions.
+    pub entry_fee_bps: u16,
+    pub exit_fee_bps: u16,
 }
 
-#[account]
-pub struct ThresholdProposal {
-    pub proposer: Pubkey,
-    pub basket: Pubkey,
-    pub proposed_threshold: u64,
-    pub yes_votes: u64,
-    pub no_votes: u64,
-    pub snapshot_supply: u64,
-    pub quorum_percentage: u8,
-    pub expiration: i64,
-    pub voters: Vec<Pubkey>,
+impl BasketConfig {
+    pub const LEN: usize = 32
+        + 4 + MAX_NAME_LEN
+        + 4 + MAX_DESCRIPTION_LEN
+        + 32 + 8 + 1
+        + 4 + 32 * MAX_ELIGIBLE_ASSETS
+        + 1 + 8 * 5 + 8
+        + 4 + 32 * MAX_WHITELIST
+        + 1 + 1
+        + 8 + 8
+        + 32 + 4 + 32 * MAX_GUARDIANS + 1 + 8
+        + 4 + ApprovalRule::LEN * MAX_APPROVAL_RULES
+        + 8 * 4 + 1 + 32
+        + 8 + 1
+        + 32 + 2 + 2;
+
+    pub fn approval_rule(&self, kind: ProposalType) -> ApprovalRule {
+        self.approval_rules
+            .iter()
+            .find(|r| r.kind == kind)
+            .cloned()
+            .unwrap_or(ApprovalRule {
+                kind,
+                approval_bps: DEFAULT_APPROVAL_BPS,
+                min_yes_bps: 0,
+            })
+    }
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VotingMode {
+    /// One unit of power, one vote.
+    Linear,
+    /// Weight is the integer square root of each voter's power, damping whales.
+    Quadratic,
+}
+
+impl VotingMode {
+    pub fn weight(&self, power: u64) -> u64 {
+        match self {
+            VotingMode::Linear => power,
+            VotingMode::Quadratic => isqrt(power),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DepositForfeit {
+    Burn,
+    Treasury,
+}
+
+/// Target weights of the basket's eligible assets. PDA (["composition", basket]).
 #[account]
-pub struct StrategyProposal {
-    pub proposer: Pubkey,
+pub struct BasketComposition {
     pub basket: Pubkey,
-    pub proposed_strategy: u8,
-    pub yes_votes: u64,
-    pub no_votes: u64,
-    pub snapshot_supply: u64,
-    pub quorum_percentage: u8,
-    pub expiration: i64,
-    pub voters: Vec<Pubkey>,
+    /// Same mints as `BasketConfig::eligible_assets`; weights sum to `TOTAL_WEIGHT_BPS`.
+    pub assets: Vec<AssetWeight>,
+}
+
+impl BasketComposition {
+    pub const LEN: usize = 32 + 4 + AssetWeight::LEN * MAX_ELIGIBLE_ASSETS;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AssetWeight {
+    pub mint: Pubkey,
+    pub weight_bps: u16,
 }
 
+impl AssetWeight {
+    pub const LEN: usize = 32 + 2;
+}
+
+/// Oracle price for one asset. PDA (["price", basket, mint]).
 #[account]
-pub struct AssetsProposal {
+pub struct PriceFeed {
+    pub basket: Pubkey,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    /// Quote units (`PRICE_DECIMALS`) per whole token; 0 until first posted.
+    pub price: u64,
+    pub updated_ts: i64,
+}
+
+impl PriceFeed {
+    pub const LEN: usize = 32 + 32 + 1 + 8 + 8;
+}
+
+/// How many yes votes a proposal kind needs to pass.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ApprovalRule {
+    pub kind: ProposalType,
+    /// Yes share of yes+no (bps) that must be exceeded.
+    pub approval_bps: u16,
+    /// Yes votes needed as a share of `snapshot_supply` (bps).
+    pub min_yes_bps: u16,
+}
+
+impl ApprovalRule {
+    pub const LEN: usize = 1 + 2 + 2;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            (DEFAULT_APPROVAL_BPS..10_000).contains(&self.approval_bps),
+            ErrorCode::InvalidParameter
+        );
+        require!(self.min_yes_bps <= 10_000, ErrorCode::InvalidParameter);
+        Ok(())
+    }
+}
+
+/// PDA (["proposal", basket, id.to_le_bytes()]).
+#[account]
+pub struct Proposal {
     pub proposer: Pubkey,
     pub basket: Pubkey,
-    pub proposed_assets: Vec<Pubkey>,
+    /// Index within the basket, from `BasketConfig::proposal_count`.
+    pub id: u64,
+    pub action: ProposalAction,
     pub yes_votes: u64,
     pub no_votes: u64,
+    pub abstain_votes: u64,
+    /// Raw veREBAL power of all votes cast; measured against quorum.
+    pub participation: u64,
+    /// Raw veREBAL power behind `yes_votes`; measured against `min_yes_bps`.
+    pub yes_power: u64,
+    /// `BasketConfig::voting_mode` at creation.
+    pub voting_mode: VotingMode,
     pub snapshot_supply: u64,
+    pub snapshot_slot: u64,
+    pub snapshot_ts: i64,
     pub quorum_percentage: u8,
+    /// `ApprovalRule` for this kind, copied at creation.
+    pub approval_bps: u16,
+    pub min_yes_bps: u16,
     pub expiration: i64,
-    pub voters: Vec<Pubkey>,
+    pub state: ProposalState,
+    /// `BasketConfig::proposal_nonce` at creation.
+    pub config_nonce: u64,
+    /// Earliest execution time once queued.
+    pub eta: i64,
+    /// Vote escrows not yet released.
+    pub open_votes: u32,
+    /// Proposer deposit still held in the deposit vault.
+    pub deposit: u64,
+}
+
+impl Proposal {
+    /// Yes must exceed `approval_bps` of yes+no (in tally weight) and its raw
+    /// power reach `min_yes_bps` of the snapshot supply; abstentions are ignored.
+    pub fn meets_approval_rule(&self) -> bool {
+        let yes = self.yes_votes as u128;
+        let decided = yes + self.no_votes as u128;
+        yes * 10_000 > decided * self.approval_bps as u128
+            && self.yes_power as u128 * 10_000
+                >= self.snapshot_supply as u128 * self.min_yes_bps as u128
+    }
+
+    fn tally_mut(&mut self, choice: VoteChoice) -> &mut u64 {
+        match choice {
+            VoteChoice::Yes => &mut self.yes_votes,
+            VoteChoice::No => &mut self.no_votes,
+            VoteChoice::Abstain => &mut self.abstain_votes,
+        }
+    }
+
+    pub fn add_votes(&mut self, choice: VoteChoice, weight: u64, power: u64) {
+        let t = self.tally_mut(choice);
+        *t = t.checked_add(weight).unwrap();
+        if choice == VoteChoice::Yes {
+            self.yes_power = self.yes_power.checked_add(power).unwrap();
+        }
+    }
+
+    pub fn remove_votes(&mut self, choice: VoteChoice, weight: u64, power: u64) {
+        let t = self.tally_mut(choice);
+        *t = t.checked_sub(weight).unwrap();
+        if choice == VoteChoice::Yes {
+            self.yes_power = self.yes_power.checked_sub(power).unwrap();
+        }
+    }
+}
+
+/// Abstentions count toward quorum only.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VoteChoice {
+    Yes,
+    No,
+    Abstain,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalState {
+    /// Open for votes until `expiration`.
+    Active,
+    /// Reached quorum and the kind's approval rule; waiting out the timelock
+    /// until `eta`.
+    Queued,
+    /// Quorum reached but not enough yes votes.
+    Defeated,
+    /// Voting closed without reaching quorum.
+    Expired,
+    /// Action applied to the basket.
+    Executed,
+    /// Withdrawn before execution.
+    Cancelled,
+    /// Blocked by the guardian council while queued.
+    Vetoed,
+}
+
+/// The basket change a proposal carries; applied by `finalize_proposal`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ProposalAction {
+    Threshold(u64),
+    Strategy(u8),
+    /// Replaces the eligible assets together with their target weights.
+    Assets(Vec<AssetWeight>),
+    /// Re‐weights the current eligible assets.
+    Weights(Vec<AssetWeight>),
+    BaseReward(u64),
+    LamportsReward(u64),
+    SlashFactor(u64),
+    CooldownSeconds(u64),
+    QuorumPercentage(u8),
+    WhitelistChange { add: Vec<Pubkey>, remove: Vec<Pubkey> },
+    /// Sets (or replaces) the approval rule for `rule.kind`.
+    ApprovalRule(ApprovalRule),
+    VotingMode(VotingMode),
+    Guardians { guardians: Vec<Pubkey>, threshold: u8 },
+    /// Instructions invoked in order, signed by the governance PDA
+    /// (["governance", basket]).
+    Execute(Vec<GovernanceInstruction>),
+    Fees { entry_bps: u16, exit_bps: u16 },
+    /// Rotates the `guardian` allowed to cancel proposals.
+    Guardian(Pubkey),
+    /// Rotates the key allowed to post asset prices.
+    OracleAuthority(Pubkey),
+}
+
+/// A serialized instruction carried by an `Execute` proposal.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GovernanceInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<GovernanceAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GovernanceAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl GovernanceInstruction {
+    pub fn space(&self) -> usize {
+        32 + 4 + 34 * self.accounts.len() + 4 + self.data.len()
+    }
+
+    fn to_instruction(&self) -> anchor_lang::solana_program::instruction::Instruction {
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: self.program_id,
+            accounts: self
+                .accounts
+                .iter()
+                .map(|m| AccountMeta {
+                    pubkey: m.pubkey,
+                    is_signer: m.is_signer,
+                    is_writable: m.is_writable,
+                })
+                .collect(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+impl ProposalAction {
+    pub fn kind(&self) -> ProposalType {
+        match self {
+            ProposalAction::Threshold(_) => ProposalType::Threshold,
+            ProposalAction::Strategy(_) => ProposalType::Strategy,
+            ProposalAction::Assets(_) => ProposalType::Assets,
+            ProposalAction::Weights(_) => ProposalType::Weights,
+            ProposalAction::BaseReward(_) => ProposalType::BaseReward,
+            ProposalAction::LamportsReward(_) => ProposalType::LamportsReward,
+            ProposalAction::SlashFactor(_) => ProposalType::SlashFactor,
+            ProposalAction::CooldownSeconds(_) => ProposalType::CooldownSeconds,
+            ProposalAction::QuorumPercentage(_) => ProposalType::QuorumPercentage,
+            ProposalAction::WhitelistChange { .. } => ProposalType::Whitelist,
+            ProposalAction::ApprovalRule(_) => ProposalType::ApprovalRule,
+            ProposalAction::VotingMode(_) => ProposalType::VotingMode,
+            ProposalAction::Guardians { .. } => ProposalType::Guardians,
+            ProposalAction::Guardian(_) => ProposalType::Guardian,
+            ProposalAction::OracleAuthority(_) => ProposalType::OracleAuthority,
+            ProposalAction::Execute(_) => ProposalType::Execute,
+            ProposalAction::Fees { .. } => ProposalType::Fees,
+        }
+    }
+
+    /// Rejects values that would break `execute_rebalance` or quorum math.
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ProposalAction::Threshold(threshold) => {
+                require!(*threshold > 0, ErrorCode::InvalidParameter)
+            }
+            ProposalAction::SlashFactor(factor) => {
+                require!(*factor >= 1, ErrorCode::InvalidParameter)
+            }
+            ProposalAction::CooldownSeconds(secs) => {
+                require!(*secs <= i64::MAX as u64, ErrorCode::InvalidParameter)
+            }
+            ProposalAction::QuorumPercentage(pct) => {
+                require!((1..=100).contains(pct), ErrorCode::InvalidParameter)
+            }
+            ProposalAction::Assets(assets) | ProposalAction::Weights(assets) => {
+                validate_weights(assets)?
+            }
+            ProposalAction::WhitelistChange { add, .. } => {
+                require!(add.len() <= MAX_WHITELIST, ErrorCode::InvalidParameter)
+            }
+            ProposalAction::ApprovalRule(rule) => rule.validate()?,
+            ProposalAction::Guardians { guardians, threshold } => {
+                validate_guardian_set(guardians, *threshold)?
+            }
+            ProposalAction::Fees { entry_bps, exit_bps } => {
+                require!(
+                    *entry_bps <= MAX_FEE_BPS && *exit_bps <= MAX_FEE_BPS,
+                    ErrorCode::InvalidParameter
+                )
+            }
+            ProposalAction::Execute(instructions) => {
+                require!(
+                    !instructions.is_empty() && instructions.len() <= MAX_GOVERNANCE_INSTRUCTIONS,
+                    ErrorCode::InvalidParameter
+                );
+                // no re‐entry into governance itself
+                require!(
+                    instructions.iter().all(|ix| ix.program_id != crate::ID),
+                    ErrorCode::InvalidParameter
+                );
+            }
+            ProposalAction::BaseReward(reward) => {
+                require!(*reward <= MAX_BASE_REWARD, ErrorCode::InvalidParameter)
+            }
+            ProposalAction::LamportsReward(reward) => {
+                require!(*reward <= MAX_LAMPORTS_REWARD, ErrorCode::InvalidParameter)
+            }
+            ProposalAction::Strategy(_)
+            | ProposalAction::VotingMode(_)
+            | ProposalAction::Guardian(_)
+            | ProposalAction::OracleAuthority(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Serialized size (enum tag + payload), used to size the proposal account.
+    pub fn space(&self) -> usize {
+        1 + match self {
+            ProposalAction::Threshold(_) => 8,
+            ProposalAction::Strategy(_) => 1,
+            ProposalAction::Assets(assets) | ProposalAction::Weights(assets) => {
+                4 + AssetWeight::LEN * assets.len()
+            }
+            ProposalAction::BaseReward(_)
+            | ProposalAction::LamportsReward(_)
+            | ProposalAction::SlashFactor(_)
+            | ProposalAction::CooldownSeconds(_) => 8,
+            ProposalAction::QuorumPercentage(_) | ProposalAction::VotingMode(_) => 1,
+            ProposalAction::WhitelistChange { add, remove } => {
+                4 + 32 * add.len() + 4 + 32 * remove.len()
+            }
+            ProposalAction::ApprovalRule(_) => ApprovalRule::LEN,
+            ProposalAction::Guardians { guardians, .. } => 4 + 32 * guardians.len() + 1,
+            ProposalAction::Guardian(_) | ProposalAction::OracleAuthority(_) => 32,
+            ProposalAction::Execute(instructions) => {
+                4 + instructions.iter().map(|ix| ix.space()).sum::<usize>()
+            }
+            ProposalAction::Fees { .. } => 2 + 2,
+        }
+    }
+
+    fn apply(
+        &self,
+        cfg: &mut Account<BasketConfig>,
+        composition: &mut Account<BasketComposition>,
+    ) -> Result<()> {
+        match self {
+            ProposalAction::Threshold(threshold) => cfg.threshold = *threshold,
+            ProposalAction::Strategy(strategy) => cfg.strategy = *strategy,
+            ProposalAction::Assets(assets) => {
+                cfg.eligible_assets = assets.iter().map(|a| a.mint).collect();
+                composition.assets = assets.clone();
+            }
+            ProposalAction::Weights(weights) => {
+                // reweights only; listing or delisting goes through `Assets`
+                require!(
+                    weights.len() == cfg.eligible_assets.len()
+                        && weights.iter().all(|w| cfg.eligible_assets.contains(&w.mint)),
+                    ErrorCode::InvalidWeights
+                );
+                composition.assets = weights.clone();
+            }
+            ProposalAction::BaseReward(reward) => cfg.base_reward = *reward,
+            ProposalAction::LamportsReward(reward) => cfg.lamports_reward = *reward,
+            ProposalAction::SlashFactor(factor) => cfg.slash_factor = *factor,
+            ProposalAction::CooldownSeconds(secs) => cfg.cooldown_seconds = *secs,
+            ProposalAction::QuorumPercentage(pct) => cfg.quorum_percentage = *pct,
+            ProposalAction::WhitelistChange { add, remove } => {
+                return apply_whitelist_change(cfg, add, remove);
+            }
+            ProposalAction::ApprovalRule(rule) => {
+                cfg.approval_rules.retain(|r| r.kind != rule.kind);
+                cfg.approval_rules.push(rule.clone());
+            }
+            ProposalAction::VotingMode(mode) => cfg.voting_mode = *mode,
+            ProposalAction::Guardians { guardians, threshold } => {
+                cfg.guardians = guardians.clone();
+                cfg.guardian_threshold = *threshold;
+            }
+            ProposalAction::Guardian(guardian) => cfg.guardian = *guardian,
+            // invoked by `execute_proposal`, which holds the accounts
+            ProposalAction::Execute(_) => {}
+            ProposalAction::Fees { entry_bps, exit_bps } => {
+                cfg.entry_fee_bps = *entry_bps;
+                cfg.exit_fee_bps = *exit_bps;
+            }
+            ProposalAction::OracleAuthority(authority) => cfg.oracle_authority = *authority,
+        }
+        Ok(())
+    }
+}
+
+/// Removes then adds whitelist entries, shared by governance and the admin fast‐path.
+fn apply_whitelist_change(cfg: &mut Account<BasketConfig>, add: &[Pubkey], remove: &[Pubkey]) -> Result<()> {
+    cfg.whitelist.retain(|bot| !remove.contains(bot));
+    for bot in add {
+        if !cfg.whitelist.contains(bot) {
+            cfg.whitelist.push(*bot);
+        }
+    }
+    require!(cfg.whitelist.len() <= MAX_WHITELIST, ErrorCode::WhitelistFull);
+    emit!(WhitelistUpdated {
+        basket: cfg.key(),
+        added: add.to_vec(),
+        removed: remove.to_vec(),
+    });
+    Ok(())
+}
+
+/// Distinct mints (at most `MAX_ELIGIBLE_ASSETS`) whose weights sum to
+/// `TOTAL_WEIGHT_BPS`; an empty basket has no weights. A zero weight winds
+/// an asset down (single‐asset redemptions drain it) so it can be delisted.
+fn validate_weights(assets: &[AssetWeight]) -> Result<()> {
+    require!(assets.len() <= MAX_ELIGIBLE_ASSETS, ErrorCode::InvalidParameter);
+    let mut total: u32 = 0;
+    for (i, a) in assets.iter().enumerate() {
+        require!(
+            assets[..i].iter().all(|b| b.mint != a.mint),
+            ErrorCode::InvalidWeights
+        );
+        total += a.weight_bps as u32;
+    }
+    require!(
+        assets.is_empty() || total == TOTAL_WEIGHT_BPS as u32,
+        ErrorCode::InvalidWeights
+    );
+    Ok(())
+}
+
+/// `amount * numerator / denominator` in u128, rounded up or down.
+fn mul_div(amount: u64, numerator: u64, denominator: u64, round_up: bool) -> u64 {
+    let product = amount as u128 * numerator as u128;
+    let denominator = denominator as u128;
+    let quotient = if round_up {
+        product.div_ceil(denominator)
+    } else {
+        product / denominator
+    };
+    u64::try_from(quotient).unwrap()
+}
+
+/// Reads an asset vault passed as a raw account, checking it is the basket's
+/// vault PDA for `mint`.
+fn load_asset_vault(info: &AccountInfo, basket: &Pubkey, mint: &Pubkey) -> Result<TokenAccount> {
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"vault", basket.as_ref(), mint.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(info.key(), expected, ErrorCode::BadVaultAccounts);
+    require_keys_eq!(*info.owner, token::ID, ErrorCode::BadVaultAccounts);
+    TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])
+}
+
+/// Checks the vaults passed to an `Assets` execution: one per listed asset,
+/// in `assets` order, each of which must already exist. Balances left in
+/// delisted vaults are moved out with `sweep_asset_vault`.
+fn check_listing_vaults(
+    accounts: &[AccountInfo],
+    basket: &Pubkey,
+    assets: &[AssetWeight],
+) -> Result<()> {
+    require!(accounts.len() == assets.len(), ErrorCode::BadVaultAccounts);
+    for (asset, info) in assets.iter().zip(accounts) {
+        load_asset_vault(info, basket, &asset.mint)?;
+    }
+    Ok(())
+}
+
+/// A vault balance with its oracle price, for NAV math.
+struct Holding {
+    amount: u64,
+    price: u64,
+    decimals: u8,
+}
+
+impl Holding {
+    fn unit(&self) -> Result<u128> {
+        Ok(10u128.checked_pow(self.decimals as u32).ok_or(ErrorCode::InvalidParameter)?)
+    }
+
+    /// Quote value of `amount` base units.
+    fn value(&self, amount: u64) -> Result<u128> {
+        Ok(amount as u128 * self.price as u128 / self.unit()?)
+    }
+
+    /// Base units worth `value`, rounded down.
+    fn amount_for(&self, value: u128) -> Result<u64> {
+        let amount = value
+            .checked_mul(self.unit()?)
+            .map(|v| v / self.price as u128)
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(ErrorCode::InvalidParameter)?;
+        Ok(amount)
+    }
+}
+
+/// Sum of asset values, rejecting overflow.
+fn checked_sum(values: &[u128]) -> Result<u128> {
+    Ok(values
+        .iter()
+        .try_fold(0u128, |total, v| total.checked_add(*v))
+        .ok_or(ErrorCode::InvalidParameter)?)
+}
+
+/// Reads `[vault, price_feed]` pairs for every eligible asset, rejecting
+/// stale or unset prices.
+fn load_holdings(
+    accounts: &[AccountInfo],
+    basket: &Pubkey,
+    assets: &[Pubkey],
+    now: i64,
+) -> Result<Vec<Holding>> {
+    require!(accounts.len() == 2 * assets.len(), ErrorCode::BadVaultAccounts);
+    let mut holdings = Vec::with_capacity(assets.len());
+    for (mint, pair) in assets.iter().zip(accounts.chunks(2)) {
+        let (vault_info, feed_info) = (&pair[0], &pair[1]);
+        let vault = load_asset_vault(vault_info, basket, mint)?;
+        let (expected_feed, _) = Pubkey::find_program_address(
+            &[b"price", basket.as_ref(), mint.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(feed_info.key(), expected_feed, ErrorCode::BadVaultAccounts);
+        require_keys_eq!(*feed_info.owner, crate::ID, ErrorCode::BadVaultAccounts);
+        let feed = PriceFeed::try_deserialize(&mut &feed_info.try_borrow_data()?[..])?;
+        require!(
+            feed.price > 0 && now.checked_sub(feed.updated_ts).unwrap() <= MAX_PRICE_AGE_SECONDS,
+            ErrorCode::StalePrice
+        );
+        holdings.push(Holding {
+            amount: vault.amount,
+            price: feed.price,
+            decimals: feed.decimals,
+        });
+    }
+    Ok(holdings)
+}
+
+fn asset_index(cfg: &BasketConfig, mint: &Pubkey) -> Result<usize> {
+    cfg.eligible_assets
+        .iter()
+        .position(|m| m == mint)
+        .ok_or_else(|| error!(ErrorCode::AssetNotEligible))
+}
+
+/// Rejects a change that leaves an asset more than `threshold` bps off its
+/// target weight, unless it moves that asset closer to target.
+fn check_weight_deviation(
+    before: &[u128],
+    after: &[u128],
+    assets: &[Pubkey],
+    composition: &BasketComposition,
+    threshold: u64,
+) -> Result<()> {
+    let nav_before = checked_sum(before)?;
+    let nav_after = checked_sum(after)?;
+    let deviation = |value: u128, nav: u128, target: u16| -> Result<u128> {
+        let weight = value
+            .checked_mul(TOTAL_WEIGHT_BPS as u128)
+            .ok_or(ErrorCode::InvalidParameter)?
+            .checked_div(nav)
+            .unwrap_or(0);
+        Ok(weight.abs_diff(target as u128))
+    };
+    for (i, mint) in assets.iter().enumerate() {
+        let target = composition
+            .assets
+            .iter()
+            .find(|a| a.mint == *mint)
+            .map_or(0, |a| a.weight_bps);
+        let old = deviation(before[i], nav_before, target)?;
+        let new = deviation(after[i], nav_after, target)?;
+        require!(
+            new <= threshold as u128 || new <= old,
+            ErrorCode::WeightDeviationExceeded
+        );
+    }
+    Ok(())
+}
+
+/// A council of at most `MAX_GUARDIANS` distinct keys with a reachable
+/// threshold, or an empty one with threshold 0.
+fn validate_guardian_set(guardians: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(guardians.len() <= MAX_GUARDIANS, ErrorCode::InvalidParameter);
+    require!(threshold as usize <= guardians.len(), ErrorCode::InvalidParameter);
+    require!(threshold > 0 || guardians.is_empty(), ErrorCode::InvalidParameter);
+    for (i, g) in guardians.iter().enumerate() {
+        require!(!guardians[..i].contains(g), ErrorCode::InvalidParameter);
+    }
+    Ok(())
+}
+
+fn round_to_week(ts: i64) -> i64 {
+    ts - ts.rem_euclid(LOCK_WEEK_SECONDS)
+}
+
+/// Floor of the square root (Newton's method).
+fn isqrt(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n / 2 + 1;
+    let mut y = (x + n / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+/// Closes an account owned by this program, sending its rent to `rent_to`.
+fn close_program_account(info: &AccountInfo, rent_to: &AccountInfo) -> Result<()> {
+    let lamports = info.lamports();
+    **rent_to.try_borrow_mut_lamports()? = rent_to.lamports().checked_add(lamports).unwrap();
+    **info.try_borrow_mut_lamports()? = 0;
+    info.assign(&System::id());
+    info.realloc(0, false)?;
+    Ok(())
+}
+
+/// Burns a forfeited proposal deposit (`sink` is the REBAL mint) or sends it
+/// to the treasury (`sink` is the treasury), per `DepositForfeit`.
+fn forfeit_deposit<'info>(
+    forfeit: DepositForfeit,
+    token_program: &AccountInfo<'info>,
+    deposit_vault: &AccountInfo<'info>,
+    escrow_auth: &AccountInfo<'info>,
+    sink: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    match forfeit {
+        DepositForfeit::Treasury => token::transfer(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                Transfer {
+                    from: deposit_vault.clone(),
+                    to: sink.clone(),
+                    authority: escrow_auth.clone(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        ),
+        DepositForfeit::Burn => token::burn(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                Burn {
+                    mint: sink.clone(),
+                    from: deposit_vault.clone(),
+                    authority: escrow_auth.clone(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        ),
+    }
+}
+
+/// Releases a vote record passed as a raw account: checks it belongs to
+/// `proposal`, drops its lock on the voter's stake and closes it, rent back
+/// to `cast_by` (the delegate paid for delegator records).
+fn release_vote_record(
+    record_info: &AccountInfo,
+    stake_info: &AccountInfo,
+    rent_to: &AccountInfo,
+    basket: &Pubkey,
+    proposal: &Pubkey,
+) -> Result<VoteRecord> {
+    require_keys_eq!(*record_info.owner, crate::ID, ErrorCode::BadRefundAccounts);
+    require_keys_eq!(*stake_info.owner, crate::ID, ErrorCode::BadRefundAccounts);
+    let record = VoteRecord::try_deserialize(&mut &record_info.try_borrow_data()?[..])?;
+    let (expected_record, _) = Pubkey::find_program_address(
+        &[record.seed(), proposal.as_ref(), record.voter.as_ref()],
+        &crate::ID,
+    );
+    let (expected_stake, _) = Pubkey::find_program_address(
+        &[b"stake", basket.as_ref(), record.voter.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(record_info.key(), expected_record, ErrorCode::BadRefundAccounts);
+    require_keys_eq!(stake_info.key(), expected_stake, ErrorCode::BadRefundAccounts);
+    require_keys_eq!(rent_to.key(), record.cast_by, ErrorCode::BadRefundAccounts);
+
+    let mut stake = StakeAccount::try_deserialize(&mut &stake_info.try_borrow_data()?[..])?;
+    stake.active_votes = stake.active_votes.checked_sub(1).unwrap();
+    stake.try_serialize(&mut &mut stake_info.try_borrow_mut_data()?[..])?;
+    close_program_account(record_info, rent_to)?;
+    Ok(record)
+}
+
+/// Creates a program‐owned PDA at `target` (like `init`, tolerating lamports
+/// already sent to the address).
+fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let current = target.lamports();
+    if current == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system.clone(),
+                system_program::CreateAccount { from: payer.clone(), to: target.clone() },
+                signer_seeds,
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        );
+    }
+    let top_up = rent.saturating_sub(current);
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system.clone(),
+                system_program::Transfer { from: payer.clone(), to: target.clone() },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system.clone(),
+            system_program::Allocate { account_to_allocate: target.clone() },
+            signer_seeds,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system.clone(),
+            system_program::Assign { account_to_assign: target.clone() },
+            signer_seeds,
+        ),
+        &crate::ID,
+    )
+}
+
+/// One per (proposal, voter) at (["vote", proposal, voter]); its `init` is
+/// what enforces one vote per voter. Delegators also get one at
+/// (["delegated_vote", proposal, delegator]), written by their delegate's
+/// `cast_vote`.
+#[account]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    /// The voter itself, or the delegate that counted this voter's power.
+    pub cast_by: Pubkey,
+    /// Tally weight under the proposal's voting mode.
+    pub weight: u64,
+    /// Raw veREBAL power behind `weight`, counted toward quorum.
+    pub power: u64,
+    pub choice: VoteChoice,
+    pub timestamp: i64,
+    /// Delegator records this vote counted (always zero on those records).
+    pub delegators: u32,
+    /// Part of `power` counted for those delegators.
+    pub delegated_power: u64,
+}
+
+impl VoteRecord {
+    pub const LEN: usize = 32 * 3 + 8 + 8 + 1 + 8 + 4 + 8;
+
+    /// Seed prefix of the PDA this record lives at.
+    pub fn seed(&self) -> &'static [u8] {
+        if self.cast_by == self.voter { b"vote" } else { b"delegated_vote" }
+    }
+}
+
+/// One per (basket, delegator): hands the delegator's voting power to `delegate`.
+#[account]
+pub struct Delegation {
+    pub basket: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    /// Locked amount delegated; 0 delegates the whole lock.
+    pub amount: u64,
+}
+
+/// Total veREBAL power of a basket, decaying like the locks behind it:
+/// power at `ts` is `bias / MAX_LOCK_SECONDS`, falling by `slope` per second
+/// until locks expire at week boundaries. PDA (["voting_supply", basket]).
+#[account]
+pub struct VotingSupply {
+    pub basket: Pubkey,
+    /// Time `bias` was last advanced to.
+    pub ts: i64,
+    /// Σ amount × (lock_end − ts) over live locks.
+    pub bias: u128,
+    /// Σ amount over live locks.
+    pub slope: u64,
+    /// Amount whose lock ends at each week, ring‐indexed by week number.
+    pub slope_changes: Vec<u64>,
+}
+
+impl VotingSupply {
+    pub const LEN: usize = 32 + 8 + 16 + 8 + 4 + 8 * SUPPLY_WEEKS;
+
+    fn week_index(end: i64) -> usize {
+        (end / LOCK_WEEK_SECONDS) as usize % SUPPLY_WEEKS
+    }
+
+    /// Decays the supply up to `now`, dropping locks as their week ends.
+    pub fn advance(&mut self, now: i64) {
+        let mut t = self.ts;
+        while t < now && self.slope > 0 {
+            let next = ((t / LOCK_WEEK_SECONDS + 1) * LOCK_WEEK_SECONDS).min(now);
+            self.bias -= self.slope as u128 * (next - t) as u128;
+            if next % LOCK_WEEK_SECONDS == 0 {
+                let i = Self::week_index(next);
+                self.slope -= self.slope_changes[i];
+                self.slope_changes[i] = 0;
+            }
+            t = next;
+        }
+        self.ts = self.ts.max(now);
+    }
+
+    pub fn power(&self) -> u64 {
+        (self.bias / MAX_LOCK_SECONDS as u128) as u64
+    }
+
+    /// Swaps a lock's `(amount, lock_end)` from `old` to `new` at `now`.
+    fn update_lock(&mut self, now: i64, old: (u64, i64), new: (u64, i64)) {
+        self.advance(now);
+        if old.1 > now {
+            self.slope -= old.0;
+            self.bias -= old.0 as u128 * (old.1 - now) as u128;
+            self.slope_changes[Self::week_index(old.1)] -= old.0;
+        }
+        if new.1 > now {
+            self.slope += new.0;
+            self.bias += new.0 as u128 * (new.1 - now) as u128;
+            self.slope_changes[Self::week_index(new.1)] += new.0;
+        }
+    }
+}
+
+/// One per (basket, staker); balance history backs snapshot voting.
+#[account]
+pub struct StakeAccount {
+    pub owner: Pubkey,
+    pub basket: Pubkey,
+    pub amount: u64,
+    /// Unix time the lock expires; voting power decays linearly towards it.
+    pub lock_end: i64,
+    /// Votes cast with this stake that have not been released yet.
+    pub active_votes: u32,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct Checkpoint {
+    pub slot: u64,
+    pub amount: u64,
+    pub lock_end: i64,
+}
+
+impl StakeAccount {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 4 + 4 + 24 * MAX_CHECKPOINTS;
+
+    /// Records the current lock at `slot`, overwriting an entry from the
+    /// same slot and dropping the oldest once full.
+    fn checkpoint(&mut self, slot: u64) {
+        match self.checkpoints.last_mut() {
+            Some(last) if last.slot == slot => {
+                last.amount = self.amount;
+                last.lock_end = self.lock_end;
+            }
+            _ => {
+                if self.checkpoints.len() == MAX_CHECKPOINTS {
+                    self.checkpoints.remove(0);
+                }
+                self.checkpoints.push(Checkpoint {
+                    slot,
+                    amount: self.amount,
+                    lock_end: self.lock_end,
+                });
+            }
+        }
+    }
+
+    /// Voting power of the lock held before `slot`, decayed to time `ts`:
+    /// `amount * remaining / MAX_LOCK_SECONDS`. Locks changed in the snapshot
+    /// slot itself do not count, and history older than the retained
+    /// checkpoints reads as 0.
+    pub fn power_at(&self, slot: u64, ts: i64) -> u64 {
+        self.capped_power_at(slot, ts, u64::MAX)
+    }
+
+    /// `power_at`, counting at most `cap` of the locked amount.
+    pub fn capped_power_at(&self, slot: u64, ts: i64, cap: u64) -> u64 {
+        self.checkpoints
+            .iter()
+            .rev()
+            .find(|c| c.slot < slot)
+            .map_or(0, |c| {
+                let remaining = c.lock_end.saturating_sub(ts).clamp(0, MAX_LOCK_SECONDS);
+                (c.amount.min(cap) as u128 * remaining as u128 / MAX_LOCK_SECONDS as u128) as u64
+            })
+    }
 }
 
 // ─── Contexts ──────────────────────────────────────────────────────────────
@@ -462,143 +2415,372 @@ pub struct AssetsProposal {
 #[derive(Accounts)]
 pub struct InitializeBasket<'info> {
     #[account(mut)] pub authority: Signer<'info>,
-    #[account(init, payer = authority, space = 8 + 32 + 4 + 64 + 4 + 256 + 1000)]
+    #[account(init, payer = authority, space = 8 + BasketConfig::LEN)]
     pub basket: Account<'info, BasketConfig>,
     pub rebal_mint: Account<'info, Mint>,
     /// PDA (["mint_auth", basket]) with bump
     pub mint_auth: UncheckedAccount<'info>,
     /// PDA (["fee_vault", basket]) with bump
     pub fee_vault: UncheckedAccount<'info>,
+    /// PDA (["escrow_auth", basket])
+    #[account(seeds = [b"escrow_auth", basket.key().as_ref()], bump)]
+    pub escrow_auth: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"stake_vault", basket.key().as_ref()],
+        bump,
+        token::mint = rebal_mint,
+        token::authority = escrow_auth,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"deposit_vault", basket.key().as_ref()],
+        bump,
+        token::mint = rebal_mint,
+        token::authority = escrow_auth,
+    )]
+    pub deposit_vault: Account<'info, TokenAccount>,
+    /// REBAL account receiving forfeited proposal deposits
+    #[account(constraint = treasury.mint == rebal_mint.key() @ ErrorCode::InvalidParameter)]
+    pub treasury: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + BasketComposition::LEN,
+        seeds = [b"composition", basket.key().as_ref()],
+        bump,
+    )]
+    pub composition: Account<'info, BasketComposition>,
+    /// PDA (["basket_auth", basket])
+    #[account(seeds = [b"basket_auth", basket.key().as_ref()], bump)]
+    pub basket_auth: UncheckedAccount<'info>,
+    /// Index shares, minted and burned by `basket_auth`
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"share_mint", basket.key().as_ref()],
+        bump,
+        mint::decimals = SHARE_DECIMALS,
+        mint::authority = basket_auth,
+    )]
+    pub share_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VotingSupply::LEN,
+        seeds = [b"voting_supply", basket.key().as_ref()],
+        bump,
+    )]
+    pub voting_supply: Account<'info, VotingSupply>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ProposeThreshold<'info> {
-    #[account(mut)] pub proposer: Signer<'info>,
-    #[account(mut)] pub basket: Account<'info, BasketConfig>,
-    pub rebal_mint: Account<'info, Mint>,
-    #[account(init, payer = proposer, space = 8 + 32*2 + 8*5 + 4 + 256)]
-    pub threshold_proposal: Account<'info, ThresholdProposal>,
+pub struct CreateStakeAccount<'info> {
+    #[account(mut)] pub owner: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + StakeAccount::LEN,
+        seeds = [b"stake", basket.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
     pub system_program: Program<'info, System>,
-    pub clock: Sysvar<'info, Clock>,
 }
 
 #[derive(Accounts)]
-pub struct VoteThreshold<'info> {
-    pub staker: Signer<'info>,
+pub struct LockRebal<'info> {
+    pub owner: Signer<'info>,
     #[account(mut)] pub basket: Account<'info, BasketConfig>,
-    #[account(mut, has_one = basket)]
-    pub threshold_proposal: Account<'info, ThresholdProposal>,
-    #[account(mut, constraint = staker_tokens.mint == basket.rebal_mint)]
-    pub staker_tokens: Account<'info, TokenAccount>,
-    #[account(mut)] pub escrow: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"voting_supply", basket.key().as_ref()], bump)]
+    pub voting_supply: Account<'info, VotingSupply>,
+    #[account(
+        mut,
+        seeds = [b"stake", basket.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+    #[account(mut, constraint = owner_tokens.mint == basket.rebal_mint)]
+    pub owner_tokens: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"stake_vault", basket.key().as_ref()], bump)]
+    pub stake_vault: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
 }
 
-impl<'info> VoteThreshold<'info> {
-    fn into_transfer_to_escrow_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        let cpi_accounts = Transfer {
-            from: self.staker_tokens.to_account_info(),
-            to: self.escrow.to_account_info(),
-            authority: self.staker.to_account_info(),
-        };
-        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
-    }
+#[derive(Accounts)]
+pub struct ExtendLock<'info> {
+    pub owner: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
+    #[account(mut, seeds = [b"voting_supply", basket.key().as_ref()], bump)]
+    pub voting_supply: Account<'info, VotingSupply>,
+    #[account(
+        mut,
+        seeds = [b"stake", basket.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
 }
 
 #[derive(Accounts)]
-pub struct FinalizeThreshold<'info> {
-    #[account(mut)] pub finalizer: Signer<'info>,
+pub struct UnlockRebal<'info> {
+    pub owner: Signer<'info>,
     #[account(mut)] pub basket: Account<'info, BasketConfig>,
-    #[account(mut, has_one = basket)]
-    pub threshold_proposal: Account<'info, ThresholdProposal>,
-    pub clock: Sysvar<'info, Clock>,
+    #[account(
+        mut,
+        seeds = [b"stake", basket.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+    #[account(
+        mut,
+        constraint = owner_tokens.mint == basket.rebal_mint,
+        constraint = owner_tokens.owner == owner.key(),
+    )]
+    pub owner_tokens: Account<'info, TokenAccount>,
+    /// PDA (["escrow_auth", basket])
+    #[account(seeds = [b"escrow_auth", basket.key().as_ref()], bump)]
+    pub escrow_auth: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"stake_vault", basket.key().as_ref()], bump)]
+    pub stake_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateVotes<'info> {
+    #[account(mut)] pub delegator: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
+    #[account(
+        init,
+        payer = delegator,
+        space = 8 + 32*3 + 8,
+        seeds = [b"delegation", basket.key().as_ref(), delegator.key().as_ref()],
+        bump,
+    )]
+    pub delegation: Account<'info, Delegation>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ProposeStrategy<'info> {
+pub struct UndelegateVotes<'info> {
+    #[account(mut)] pub delegator: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
+    #[account(
+        mut,
+        close = delegator,
+        seeds = [b"delegation", basket.key().as_ref(), delegator.key().as_ref()],
+        bump,
+        has_one = delegator,
+    )]
+    pub delegation: Account<'info, Delegation>,
+}
+
+#[derive(Accounts)]
+#[instruction(action: ProposalAction)]
+pub struct CreateProposal<'info> {
     #[account(mut)] pub proposer: Signer<'info>,
     #[account(mut)] pub basket: Account<'info, BasketConfig>,
-    pub rebal_mint: Account<'info, Mint>,
-    #[account(init, payer = proposer, space = 8 + 32*2 + 8*5 + 4 + 256)]
-    pub strategy_proposal: Account<'info, StrategyProposal>,
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + 32*2 + 8 + action.space() + 8*9 + 1 + 1 + 2 + 2 + 1 + 8 + 8 + 4 + 8,
+        seeds = [b"proposal", basket.key().as_ref(), &basket.proposal_count.to_le_bytes()],
+        bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, seeds = [b"voting_supply", basket.key().as_ref()], bump)]
+    pub voting_supply: Account<'info, VotingSupply>,
+    #[account(seeds = [b"stake", basket.key().as_ref(), proposer.key().as_ref()], bump)]
+    pub proposer_stake: Account<'info, StakeAccount>,
+    #[account(
+        mut,
+        constraint = proposer_tokens.mint == basket.rebal_mint,
+        constraint = proposer_tokens.owner == proposer.key(),
+    )]
+    pub proposer_tokens: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"deposit_vault", basket.key().as_ref()], bump)]
+    pub deposit_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub clock: Sysvar<'info, Clock>,
 }
 
 #[derive(Accounts)]
-pub struct VoteStrategy<'info> {
-    pub staker: Signer<'info>,
+pub struct CastVote<'info> {
+    #[account(mut)] pub staker: Signer<'info>,
     #[account(mut)] pub basket: Account<'info, BasketConfig>,
     #[account(mut, has_one = basket)]
-    pub strategy_proposal: Account<'info, StrategyProposal>,
-    #[account(mut, constraint = staker_tokens.mint == basket.rebal_mint)]
-    pub staker_tokens: Account<'info, TokenAccount>,
-    #[account(mut)] pub escrow: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        seeds = [b"stake", basket.key().as_ref(), staker.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+    /// PDA (["delegation", basket, staker]); its power is not the staker's to vote
+    #[account(seeds = [b"delegation", basket.key().as_ref(), staker.key().as_ref()], bump)]
+    pub delegation: UncheckedAccount<'info>,
+    /// PDA (["delegated_vote", proposal, staker]); power a delegate already counted
+    #[account(seeds = [b"delegated_vote", proposal.key().as_ref(), staker.key().as_ref()], bump)]
+    pub delegated_record: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = staker,
+        space = 8 + VoteRecord::LEN,
+        seeds = [b"vote", proposal.key().as_ref(), staker.key().as_ref()],
+        bump,
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    pub system_program: Program<'info, System>,
 }
 
-impl<'info> VoteStrategy<'info> {
-    fn into_transfer_to_escrow_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        let cpi_accounts = Transfer {
-            from: self.staker_tokens.to_account_info(),
-            to: self.escrow.to_account_info(),
-            authority: self.staker.to_account_info(),
-        };
-        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
-    }
+#[derive(Accounts)]
+pub struct ChangeVote<'info> {
+    pub staker: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
+    #[account(mut, has_one = basket)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        seeds = [b"vote", proposal.key().as_ref(), staker.key().as_ref()],
+        bump,
+        has_one = proposal,
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
 }
 
 #[derive(Accounts)]
-pub struct FinalizeStrategy<'info> {
-    #[account(mut)] pub finalizer: Signer<'info>,
-    #[account(mut)] pub basket: Account<'info, BasketConfig>,
+pub struct RetractVote<'info> {
+    #[account(mut)] pub staker: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
     #[account(mut, has_one = basket)]
-    pub strategy_proposal: Account<'info, StrategyProposal>,
-    pub clock: Sysvar<'info, Clock>,
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        close = staker,
+        seeds = [b"vote", proposal.key().as_ref(), staker.key().as_ref()],
+        bump,
+        has_one = proposal,
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    #[account(
+        mut,
+        seeds = [b"stake", basket.key().as_ref(), staker.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
 }
 
 #[derive(Accounts)]
-pub struct ProposeAssets<'info> {
-    #[account(mut)] pub proposer: Signer<'info>,
+pub struct FinalizeProposal<'info> {
+    #[account(mut)] pub finalizer: Signer<'info>,
     #[account(mut)] pub basket: Account<'info, BasketConfig>,
+    #[account(mut, has_one = basket)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        constraint = proposer_tokens.mint == basket.rebal_mint,
+        constraint = proposer_tokens.owner == proposal.proposer,
+    )]
+    pub proposer_tokens: Account<'info, TokenAccount>,
+    /// PDA (["escrow_auth", basket])
+    #[account(seeds = [b"escrow_auth", basket.key().as_ref()], bump)]
+    pub escrow_auth: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"deposit_vault", basket.key().as_ref()], bump)]
+    pub deposit_vault: Account<'info, TokenAccount>,
+    /// Receives forfeited deposits; only read when one is sent there
+    #[account(mut, address = basket.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+    #[account(mut, address = basket.rebal_mint)]
     pub rebal_mint: Account<'info, Mint>,
-    #[account(init, payer = proposer, space = 8 + 32*2 + 8*2 + 4 + 512)]
-    pub assets_proposal: Account<'info, AssetsProposal>,
-    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
     pub clock: Sysvar<'info, Clock>,
 }
 
 #[derive(Accounts)]
-pub struct VoteAssets<'info> {
-    pub staker: Signer<'info>,
+pub struct ExecuteProposal<'info> {
+    #[account(mut)] pub executor: Signer<'info>,
     #[account(mut)] pub basket: Account<'info, BasketConfig>,
     #[account(mut, has_one = basket)]
-    pub assets_proposal: Account<'info, AssetsProposal>,
-    #[account(mut, constraint = staker_tokens.mint == basket.rebal_mint)]
-    pub staker_tokens: Account<'info, TokenAccount>,
-    #[account(mut)] pub escrow: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, seeds = [b"composition", basket.key().as_ref()], bump)]
+    pub composition: Account<'info, BasketComposition>,
+    /// PDA (["governance", basket]); signs `Execute` proposal instructions
+    #[account(mut, seeds = [b"governance", basket.key().as_ref()], bump)]
+    pub governance: UncheckedAccount<'info>,
 }
 
-impl<'info> VoteAssets<'info> {
-    fn into_transfer_to_escrow_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        let cpi_accounts = Transfer {
-            from: self.staker_tokens.to_account_info(),
-            to: self.escrow.to_account_info(),
-            authority: self.staker.to_account_info(),
-        };
-        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
-    }
+#[derive(Accounts)]
+pub struct UpdateWhitelist<'info> {
+    pub initializer: Signer<'info>,
+    #[account(mut, has_one = initializer)]
+    pub basket: Account<'info, BasketConfig>,
 }
 
 #[derive(Accounts)]
-pub struct FinalizeAssets<'info> {
-    #[account(mut)] pub finalizer: Signer<'info>,
-    #[account(mut)] pub basket: Account<'info, BasketConfig>,
+pub struct WithdrawVoteEscrow<'info> {
+    #[account(mut)] pub staker: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
     #[account(mut, has_one = basket)]
-    pub assets_proposal: Account<'info, AssetsProposal>,
-    pub clock: Sysvar<'info, Clock>,
+    pub proposal: Account<'info, Proposal>,
+    /// The staker's direct or delegated vote record
+    #[account(
+        mut,
+        close = rent_receiver,
+        has_one = proposal,
+        constraint = vote_record.voter == staker.key() @ ErrorCode::BadRefundAccounts,
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    /// Receives the record rent: the delegate paid it for delegator records
+    #[account(mut, address = vote_record.cast_by)]
+    pub rent_receiver: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"stake", basket.key().as_ref(), staker.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+}
+
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    pub authority: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
+    #[account(mut, has_one = basket)]
+    pub proposal: Account<'info, Proposal>,
+    /// Receives the proposal's rent once it is closed
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = proposer_tokens.mint == basket.rebal_mint,
+        constraint = proposer_tokens.owner == proposal.proposer,
+    )]
+    pub proposer_tokens: Account<'info, TokenAccount>,
+    /// PDA (["escrow_auth", basket])
+    #[account(seeds = [b"escrow_auth", basket.key().as_ref()], bump)]
+    pub escrow_auth: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"deposit_vault", basket.key().as_ref()], bump)]
+    pub deposit_vault: Account<'info, TokenAccount>,
+    /// Receives deposits a guardian cancel forfeits under `DepositForfeit::Treasury`
+    #[account(mut, address = basket.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+    #[account(mut, address = basket.rebal_mint)]
+    pub rebal_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct VetoProposal<'info> {
+    pub guardian: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
+    #[account(mut, has_one = basket)]
+    pub proposal: Account<'info, Proposal>,
 }
 
 #[derive(Accounts)]
@@ -617,11 +2799,165 @@ pub struct ExecuteRebalance<'info> {
     pub clock: Sysvar<'info, Clock>,
 }
 
+#[derive(Accounts)]
+pub struct CreateAssetVault<'info> {
+    #[account(mut)] pub payer: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
+    pub mint: Account<'info, Mint>,
+    /// `Assets` proposal listing `mint`, when it is not eligible yet
+    #[account(has_one = basket)]
+    pub proposal: Option<Account<'info, Proposal>>,
+    /// PDA (["basket_auth", basket]); owns every asset vault of the basket
+    #[account(seeds = [b"basket_auth", basket.key().as_ref()], bump)]
+    pub basket_auth: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"vault", basket.key().as_ref(), mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = basket_auth,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PriceFeed::LEN,
+        seeds = [b"price", basket.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SweepAssetVault<'info> {
+    pub basket: Account<'info, BasketConfig>,
+    pub mint: Account<'info, Mint>,
+    #[account(seeds = [b"share_mint", basket.key().as_ref()], bump)]
+    pub share_mint: Account<'info, Mint>,
+    /// PDA (["basket_auth", basket])
+    #[account(seeds = [b"basket_auth", basket.key().as_ref()], bump)]
+    pub basket_auth: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"vault", basket.key().as_ref(), mint.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    /// PDA (["governance", basket])
+    #[account(seeds = [b"governance", basket.key().as_ref()], bump)]
+    pub governance: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = destination.mint == mint.key(),
+        constraint = destination.owner == governance.key(),
+    )]
+    pub destination: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DepositInKind<'info> {
+    pub owner: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
+    /// PDA (["basket_auth", basket])
+    #[account(seeds = [b"basket_auth", basket.key().as_ref()], bump)]
+    pub basket_auth: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"share_mint", basket.key().as_ref()], bump)]
+    pub share_mint: Account<'info, Mint>,
+    #[account(mut, constraint = owner_shares.mint == share_mint.key())]
+    pub owner_shares: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemInKind<'info> {
+    pub owner: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
+    /// PDA (["basket_auth", basket])
+    #[account(seeds = [b"basket_auth", basket.key().as_ref()], bump)]
+    pub basket_auth: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"share_mint", basket.key().as_ref()], bump)]
+    pub share_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = owner_shares.mint == share_mint.key(),
+        constraint = owner_shares.owner == owner.key(),
+    )]
+    pub owner_shares: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePrice<'info> {
+    pub oracle_authority: Signer<'info>,
+    #[account(has_one = oracle_authority)]
+    pub basket: Account<'info, BasketConfig>,
+    #[account(mut, has_one = basket)]
+    pub price_feed: Account<'info, PriceFeed>,
+}
+
+#[derive(Accounts)]
+pub struct DepositSingle<'info> {
+    pub owner: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
+    #[account(seeds = [b"composition", basket.key().as_ref()], bump)]
+    pub composition: Account<'info, BasketComposition>,
+    /// PDA (["basket_auth", basket])
+    #[account(seeds = [b"basket_auth", basket.key().as_ref()], bump)]
+    pub basket_auth: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"share_mint", basket.key().as_ref()], bump)]
+    pub share_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = owner_shares.mint == share_mint.key(),
+        constraint = owner_shares.owner == owner.key(),
+    )]
+    pub owner_shares: Account<'info, TokenAccount>,
+    /// The asset deposited or redeemed
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = owner_tokens.mint == mint.key(),
+        constraint = owner_tokens.owner == owner.key(),
+    )]
+    pub owner_tokens: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemSingle<'info> {
+    pub owner: Signer<'info>,
+    pub basket: Account<'info, BasketConfig>,
+    #[account(seeds = [b"composition", basket.key().as_ref()], bump)]
+    pub composition: Account<'info, BasketComposition>,
+    /// PDA (["basket_auth", basket])
+    #[account(seeds = [b"basket_auth", basket.key().as_ref()], bump)]
+    pub basket_auth: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"share_mint", basket.key().as_ref()], bump)]
+    pub share_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = owner_shares.mint == share_mint.key(),
+        constraint = owner_shares.owner == owner.key(),
+    )]
+    pub owner_shares: Account<'info, TokenAccount>,
+    /// The asset deposited or redeemed
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = owner_tokens.mint == mint.key(),
+        constraint = owner_tokens.owner == owner.key(),
+    )]
+    pub owner_tokens: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
 // ─── Events & Errors ───────────────────────────────────────────────────────
 
 #[event]
 pub struct ProposalCreated {
     pub basket: Pubkey,
+    pub proposal: Pubkey,
+    pub id: u64,
     pub kind: ProposalType,
     pub proposer: Pubkey,
     pub expiration: i64,
@@ -633,7 +2969,18 @@ pub struct Voted {
     pub kind: ProposalType,
     pub voter: Pubkey,
     pub weight: u64,
-    pub accept: bool,
+    pub choice: VoteChoice,
+}
+
+/// `current` is `None` when the vote was retracted.
+#[event]
+pub struct VoteChanged {
+    pub basket: Pubkey,
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub weight: u64,
+    pub previous: VoteChoice,
+    pub current: Option<VoteChoice>,
 }
 
 #[event]
@@ -643,6 +2990,65 @@ pub struct ProposalFinalized {
     pub approved: bool,
 }
 
+#[event]
+pub struct ProposalQueued {
+    pub basket: Pubkey,
+    pub proposal: Pubkey,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub basket: Pubkey,
+    pub proposal: Pubkey,
+    pub kind: ProposalType,
+}
+
+#[event]
+pub struct ProposalVetoed {
+    pub basket: Pubkey,
+    pub proposal: Pubkey,
+    /// Council members whose signatures carried the veto.
+    pub guardians: Vec<Pubkey>,
+}
+
+#[event]
+pub struct ProposalCancelled {
+    pub basket: Pubkey,
+    pub proposal: Pubkey,
+    pub cancelled_by: Pubkey,
+}
+
+/// `amounts` follows `eligible_assets` order.
+#[event]
+pub struct SharesMinted {
+    pub basket: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub amounts: Vec<u64>,
+    /// Quote value (`PRICE_DECIMALS`) kept by the basket; 0 for in‐kind.
+    pub fee: u64,
+}
+
+/// `amounts` follows `eligible_assets` order.
+#[event]
+pub struct SharesBurned {
+    pub basket: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub amounts: Vec<u64>,
+    /// Quote value (`PRICE_DECIMALS`) kept by the basket; 0 for in‐kind.
+    pub fee: u64,
+}
+
+#[event]
+pub struct PriceUpdated {
+    pub basket: Pubkey,
+    pub mint: Pubkey,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct RebalanceExecuted {
     pub basket: Pubkey,
@@ -652,11 +3058,88 @@ pub struct RebalanceExecuted {
     pub timestamp: i64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[event]
+pub struct StakeChanged {
+    pub basket: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub lock_end: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct VotesDelegated {
+    pub basket: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DepositReleased {
+    pub basket: Pubkey,
+    pub proposal: Pubkey,
+    pub amount: u64,
+    /// False when the deposit was burned or sent to the treasury.
+    pub refunded: bool,
+}
+
+#[event]
+pub struct VotesUndelegated {
+    pub basket: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+}
+
+#[event]
+pub struct VoteEscrowWithdrawn {
+    pub basket: Pubkey,
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    /// Raw veREBAL power the released vote had counted.
+    pub power: u64,
+}
+
+#[event]
+pub struct AssetVaultCreated {
+    pub basket: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct AssetVaultSwept {
+    pub basket: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct WhitelistUpdated {
+    pub basket: Pubkey,
+    pub added: Vec<Pubkey>,
+    pub removed: Vec<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum ProposalType {
     Threshold,
     Strategy,
     Assets,
+    Weights,
+    BaseReward,
+    LamportsReward,
+    SlashFactor,
+    CooldownSeconds,
+    QuorumPercentage,
+    Whitelist,
+    ApprovalRule,
+    VotingMode,
+    Guardians,
+    Execute,
+    Fees,
+    Guardian,
+    OracleAuthority,
 }
 
 #[error_code]
@@ -668,4 +3151,108 @@ pub enum ErrorCode {
     #[msg("Cooldown still active")] CooldownActive,
     #[msg("Bot not whitelisted")] NotWhitelisted,
     #[msg("Proposal does not belong to this basket")] BadBasket,
+    #[msg("Proposal is still open for voting")] ProposalStillActive,
+    #[msg("Parameter out of bounds")] InvalidParameter,
+    #[msg("Whitelist is full")] WhitelistFull,
+    #[msg("Proposal is no longer active")] ProposalNotActive,
+    #[msg("Proposal was already executed")] ProposalAlreadyExecuted,
+    #[msg("Basket config changed since this proposal was created")] StaleProposal,
+    #[msg("Proposal is not queued for execution")] ProposalNotQueued,
+    #[msg("Timelock has not elapsed")] TimelockActive,
+    #[msg("Signer may not cancel this proposal")] CancelNotAllowed,
+    #[msg("Refund accounts do not match the proposal's escrows")] BadRefundAccounts,
+    #[msg("No stake at the proposal snapshot")] NoVotingPower,
+    #[msg("Stake is locked by unreleased votes")] VotesOutstanding,
+    #[msg("Not enough stake")] InsufficientStake,
+    #[msg("Lock end must be in the future and within the maximum lock")] InvalidLockEnd,
+    #[msg("Lock has not expired")] LockActive,
+    #[msg("Voting power is delegated")] VotesDelegated,
+    #[msg("Delegation accounts do not match")] BadDelegationAccounts,
+    #[msg("Voting period is outside the basket's bounds")] InvalidVotingPeriod,
+    #[msg("Not enough guardian signatures to veto")] NotEnoughGuardians,
+    #[msg("Asset weights must be distinct mints summing to 10,000 bps")] InvalidWeights,
+    #[msg("Mint is not an eligible asset of this basket")] AssetNotEligible,
+    #[msg("Vault accounts do not match the basket's assets")] BadVaultAccounts,
+    #[msg("Amount is outside the caller's slippage limit")] SlippageExceeded,
+    #[msg("Oracle price is missing or stale")] StalePrice,
+    #[msg("Basket has no shares outstanding; seed it in kind first")] BasketNotSeeded,
+    #[msg("Vault does not hold enough of the asset")] InsufficientLiquidity,
+    #[msg("Asset weight would deviate too far from its target")] WeightDeviationExceeded,
+    #[msg("Asset vault still holds tokens")] VaultNotEmpty,
+    #[msg("Asset is still listed and the basket has shares outstanding")] AssetStillListed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stake(amount: u64, lock_end: i64) -> StakeAccount {
+        let mut stake = StakeAccount {
+            owner: Pubkey::new_unique(),
+            basket: Pubkey::default(),
+            amount,
+            lock_end,
+            active_votes: 0,
+            checkpoints: Vec::new(),
+        };
+        stake.checkpoint(0);
+        stake
+    }
+
+    /// `power()` is the floor of the summed exact powers, so it sits within
+    /// one unit per stake of the summed (individually floored) `power_at`.
+    fn assert_matches(supply: &VotingSupply, stakes: &[StakeAccount], slot: u64, now: i64) {
+        let summed: u64 = stakes.iter().map(|s| s.power_at(slot, now)).sum();
+        let power = supply.power();
+        assert!(
+            power >= summed && power < summed + stakes.len() as u64,
+            "at {now}: supply {power}, stakes {summed}"
+        );
+    }
+
+    #[test]
+    fn voting_supply_decays_with_the_stakes_across_weeks() {
+        let week = LOCK_WEEK_SECONDS;
+        let t0 = 2_900 * week + 3 * 86_400 + 1_234;
+        let mut supply = VotingSupply {
+            basket: Pubkey::default(),
+            ts: t0,
+            bias: 0,
+            slope: 0,
+            slope_changes: vec![0; SUPPLY_WEEKS],
+        };
+        let mut stakes = vec![
+            stake(1_000_000_000, round_to_week(t0 + 2 * week)),
+            stake(5_000_000, round_to_week(t0 + 52 * week)),
+            stake(777_777, round_to_week(t0 + MAX_LOCK_SECONDS)),
+        ];
+        for s in &stakes {
+            supply.update_lock(t0, (0, 0), (s.amount, s.lock_end));
+        }
+
+        // 1) decay inside a week, across the first expiry and several boundaries
+        for now in [t0, t0 + week - 1, t0 + 2 * week, t0 + 3 * week + 17] {
+            supply.advance(now);
+            assert_matches(&supply, &stakes, 1, now);
+        }
+        assert_eq!(supply.slope, 5_000_000 + 777_777);
+
+        // 2) extending a live lock moves its slope change to the new week
+        let now = t0 + 3 * week + 17;
+        let s = &mut stakes[1];
+        let old = (s.amount, s.lock_end);
+        s.lock_end = round_to_week(now + 100 * week);
+        s.checkpoint(1);
+        supply.update_lock(now, old, (s.amount, s.lock_end));
+        assert_matches(&supply, &stakes, 2, now);
+
+        // 3) past the old end, then past the slot reused by the ring, to zero
+        for now in [t0 + 60 * week, t0 + 150 * week, t0 + MAX_LOCK_SECONDS - week] {
+            supply.advance(now);
+            assert_matches(&supply, &stakes, 2, now);
+        }
+        supply.advance(t0 + MAX_LOCK_SECONDS + week);
+        assert_eq!(supply.slope, 0);
+        assert_eq!(supply.power(), 0);
+    }
 }
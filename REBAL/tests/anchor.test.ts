@@ -31,9 +31,18 @@ describe("REBAL Program", () => {
         splToken.TOKEN_PROGRAM_ID
       )
     );
-    await pg.connection.sendTransaction(txInitMint, [mintKp]);
+    const mintSig = await pg.connection.sendTransaction(txInitMint, [mintKp]);
+    await pg.connection.confirmTransaction(mintSig);
 
-    // 3) Derive the two PDAs your program expects
+    // c) REBAL treasury for forfeited proposal deposits
+    const treasury = await splToken.createAccount(
+      pg.connection,
+      pg.wallet.keypair,
+      mintKp.publicKey,
+      pg.wallet.publicKey
+    );
+
+    // 3) Derive the PDAs your program expects
     const [mintAuthPda, mintAuthBump] = await web3.PublicKey.findProgramAddress(
       [Buffer.from("mint_auth"), basketKp.publicKey.toBuffer()],
       pg.program.programId
@@ -42,6 +51,34 @@ describe("REBAL Program", () => {
       [Buffer.from("fee_vault"), basketKp.publicKey.toBuffer()],
       pg.program.programId
     );
+    const [escrowAuthPda] = await web3.PublicKey.findProgramAddress(
+      [Buffer.from("escrow_auth"), basketKp.publicKey.toBuffer()],
+      pg.program.programId
+    );
+    const [stakeVaultPda] = await web3.PublicKey.findProgramAddress(
+      [Buffer.from("stake_vault"), basketKp.publicKey.toBuffer()],
+      pg.program.programId
+    );
+    const [depositVaultPda] = await web3.PublicKey.findProgramAddress(
+      [Buffer.from("deposit_vault"), basketKp.publicKey.toBuffer()],
+      pg.program.programId
+    );
+    const [compositionPda] = await web3.PublicKey.findProgramAddress(
+      [Buffer.from("composition"), basketKp.publicKey.toBuffer()],
+      pg.program.programId
+    );
+    const [basketAuthPda] = await web3.PublicKey.findProgramAddress(
+      [Buffer.from("basket_auth"), basketKp.publicKey.toBuffer()],
+      pg.program.programId
+    );
+    const [shareMintPda] = await web3.PublicKey.findProgramAddress(
+      [Buffer.from("share_mint"), basketKp.publicKey.toBuffer()],
+      pg.program.programId
+    );
+    const [votingSupplyPda] = await web3.PublicKey.findProgramAddress(
+      [Buffer.from("voting_supply"), basketKp.publicKey.toBuffer()],
+      pg.program.programId
+    );
 
     // 4) Fund the fee vault PDA so execute_rebalance tests later won't run out of lamports
     const airdropSig = await pg.connection.requestAirdrop(
@@ -55,12 +92,27 @@ describe("REBAL Program", () => {
     const description    = "A test basket";
     const threshold      = new BN(5);
     const strategy       = 0;
-    const eligibleAssets = [mintKp.publicKey];
+    const eligibleAssets = [{ mint: mintKp.publicKey, weightBps: 10_000 }];
     const quorum         = 10;
     const cooldown       = new BN(60);
     const baseReward     = new BN(1000);
     const lamportsReward = new BN(1_000);
     const slashFactor    = new BN(2);
+    const executionDelay = new BN(86_400);
+    const guardian       = pg.wallet.publicKey;
+    const guardians      = [pg.wallet.publicKey];
+    const guardianThreshold = 1;
+    const approvalRules  = [
+      { kind: { assets: {} }, approvalBps: 6_600, minYesBps: 2_000 },
+    ];
+    const proposalDeposit    = new BN(100);
+    const minProposerBalance = new BN(1_000);
+    const minVotingPeriod    = new BN(86_400);
+    const maxVotingPeriod    = new BN(14 * 86_400);
+    const depositForfeit     = { treasury: {} };
+    const oracleAuthority    = pg.wallet.publicKey;
+    const entryFeeBps        = 30;
+    const exitFeeBps         = 50;
 
     const tx2 = await pg.program.methods
       .initializeBasket(
@@ -74,6 +126,19 @@ describe("REBAL Program", () => {
         baseReward,
         lamportsReward,
         slashFactor,
+        executionDelay,
+        guardian,
+        guardians,
+        guardianThreshold,
+        approvalRules,
+        proposalDeposit,
+        minProposerBalance,
+        minVotingPeriod,
+        maxVotingPeriod,
+        depositForfeit,
+        oracleAuthority,
+        entryFeeBps,
+        exitFeeBps,
         mintAuthBump,
         feeVaultBump
       )
@@ -83,6 +148,15 @@ describe("REBAL Program", () => {
         rebalMint:     mintKp.publicKey,
         mintAuth:      mintAuthPda,
         feeVault:      feeVaultPda,
+        escrowAuth:    escrowAuthPda,
+        stakeVault:    stakeVaultPda,
+        depositVault:  depositVaultPda,
+        treasury,
+        composition:   compositionPda,
+        basketAuth:    basketAuthPda,
+        shareMint:     shareMintPda,
+        votingSupply:  votingSupplyPda,
+        tokenProgram:  splToken.TOKEN_PROGRAM_ID,
         systemProgram: web3.SystemProgram.programId,
       })
       .signers([basketKp])
@@ -101,5 +175,685 @@ describe("REBAL Program", () => {
       basket.eligibleAssets[0].toBase58(),
       mintKp.publicKey.toBase58()
     );
+    assert.ok(basket.executionDelaySeconds.eq(executionDelay));
+    assert.equal(basket.guardian.toBase58(), guardian.toBase58());
+    assert.equal(basket.guardians.length, 1);
+    assert.equal(basket.guardianThreshold, guardianThreshold);
+    assert.ok(basket.totalStaked.eqn(0));
+    assert.ok(basket.proposalCount.eqn(0));
+    assert.equal(basket.approvalRules.length, 1);
+    assert.equal(basket.approvalRules[0].approvalBps, 6_600);
+    assert.ok(basket.proposalDeposit.eq(proposalDeposit));
+    assert.ok(basket.maxVotingPeriod.eq(maxVotingPeriod));
+    assert.equal(basket.treasury.toBase58(), treasury.toBase58());
+    assert.equal(basket.oracleAuthority.toBase58(), oracleAuthority.toBase58());
+    assert.equal(basket.entryFeeBps, entryFeeBps);
+    assert.equal(basket.exitFeeBps, exitFeeBps);
+
+    const composition = await pg.program.account.basketComposition.fetch(
+      compositionPda
+    );
+    assert.equal(composition.assets.length, 1);
+    assert.equal(composition.assets[0].weightBps, 10_000);
+  });
+
+  // ─── Share helpers ───────────────────────────────────────────────────────
+
+  const payer = () => pg.wallet.keypair;
+  const pda = (...seeds: Buffer[]) =>
+    web3.PublicKey.findProgramAddressSync(seeds, pg.program.programId)[0];
+  const balance = async (account: web3.PublicKey) =>
+    Number((await splToken.getAccount(pg.connection, account)).amount);
+  const writable = (...keys: web3.PublicKey[]) =>
+    keys.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+
+  // Basket over `assets` with the wallet as oracle and guardian; returns its
+  // share PDAs and REBAL accounts.
+  async function initBasket(
+    assets: { mint: web3.PublicKey; weightBps: number }[],
+    threshold: number,
+    {
+      entryFeeBps = 0,
+      exitFeeBps = 0,
+      executionDelay = 86_400,
+      proposalDeposit = 0,
+      minVotingPeriod = 86_400,
+      depositForfeit = { burn: {} } as object,
+    } = {}
+  ) {
+    const basketKp = web3.Keypair.generate();
+    const basket = basketKp.publicKey;
+    const rebalMint = await splToken.createMint(
+      pg.connection, payer(), pg.wallet.publicKey, null, 6
+    );
+    const treasury = await splToken.createAccount(
+      pg.connection, payer(), rebalMint, pg.wallet.publicKey
+    );
+    const [mintAuth, mintAuthBump] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_auth"), basket.toBuffer()],
+      pg.program.programId
+    );
+    const [feeVault, feeVaultBump] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault"), basket.toBuffer()],
+      pg.program.programId
+    );
+    const tag = (s: string) => pda(Buffer.from(s), basket.toBuffer());
+
+    await pg.program.methods
+      .initializeBasket(
+        "Share Basket",
+        "Share tests",
+        new BN(threshold),
+        0,
+        assets,
+        10,
+        new BN(60),
+        new BN(1_000),
+        new BN(1_000),
+        new BN(2),
+        new BN(executionDelay),
+        pg.wallet.publicKey,
+        [pg.wallet.publicKey],
+        1,
+        [],
+        new BN(proposalDeposit),
+        new BN(0),
+        new BN(minVotingPeriod),
+        new BN(14 * 86_400),
+        depositForfeit,
+        pg.wallet.publicKey,
+        entryFeeBps,
+        exitFeeBps,
+        mintAuthBump,
+        feeVaultBump
+      )
+      .accounts({
+        authority:     pg.wallet.publicKey,
+        basket,
+        rebalMint,
+        mintAuth,
+        feeVault,
+        escrowAuth:    tag("escrow_auth"),
+        stakeVault:    tag("stake_vault"),
+        depositVault:  tag("deposit_vault"),
+        treasury,
+        composition:   tag("composition"),
+        basketAuth:    tag("basket_auth"),
+        shareMint:     tag("share_mint"),
+        votingSupply:  tag("voting_supply"),
+        tokenProgram:  splToken.TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([basketKp])
+      .rpc();
+
+    const shareMint = tag("share_mint");
+    const ownerShares = await splToken.createAccount(
+      pg.connection, payer(), shareMint, pg.wallet.publicKey
+    );
+    return {
+      basket,
+      composition: tag("composition"),
+      basketAuth: tag("basket_auth"),
+      shareMint,
+      ownerShares,
+      rebalMint,
+      treasury,
+    };
+  }
+
+  // Asset mint with `supply` base units in the wallet's token account.
+  async function fundedMint(decimals: number, supply: number) {
+    const mint = await splToken.createMint(
+      pg.connection, payer(), pg.wallet.publicKey, null, decimals
+    );
+    const owned = await splToken.createAccount(
+      pg.connection, payer(), mint, pg.wallet.publicKey
+    );
+    await splToken.mintTo(pg.connection, payer(), mint, owned, payer(), supply);
+    return { mint, owned };
+  }
+
+  async function createVault(basket: web3.PublicKey, mint: web3.PublicKey) {
+    const vault = pda(Buffer.from("vault"), basket.toBuffer(), mint.toBuffer());
+    const priceFeed = pda(Buffer.from("price"), basket.toBuffer(), mint.toBuffer());
+    await pg.program.methods
+      .createAssetVault()
+      .accounts({
+        payer:         pg.wallet.publicKey,
+        basket,
+        mint,
+        proposal:      null,
+        basketAuth:    pda(Buffer.from("basket_auth"), basket.toBuffer()),
+        vault,
+        priceFeed,
+        tokenProgram:  splToken.TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    return { vault, priceFeed };
+  }
+
+  // ─── In‐kind shares ──────────────────────────────────────────────────────
+
+  it("deposits and redeems basket shares in kind", async () => {
+    // 1) two assets at 50/50, 6 and 9 decimals
+    const { mint: mintA, owned: ownedA } = await fundedMint(6, 1_000_000_000);
+    const { mint: mintB, owned: ownedB } = await fundedMint(9, 1_000_000_000_000);
+    const b = await initBasket(
+      [
+        { mint: mintA, weightBps: 5_000 },
+        { mint: mintB, weightBps: 5_000 },
+      ],
+      500
+    );
+    const { vault: vaultA } = await createVault(b.basket, mintA);
+    const { vault: vaultB } = await createVault(b.basket, mintB);
+    const pairs = writable(vaultA, ownedA, vaultB, ownedB);
+    const shareAccounts = {
+      owner:        pg.wallet.publicKey,
+      basket:       b.basket,
+      basketAuth:   b.basketAuth,
+      shareMint:    b.shareMint,
+      ownerShares:  b.ownerShares,
+      tokenProgram: splToken.TOKEN_PROGRAM_ID,
+    };
+
+    // 2) first deposit (no shares yet) takes max_amounts as the ratio
+    await pg.program.methods
+      .depositInKind(new BN(1_000_000), [new BN(100_000_000), new BN(50_000_000_000)])
+      .accounts(shareAccounts)
+      .remainingAccounts(pairs)
+      .rpc();
+    assert.equal(await balance(vaultA), 100_000_000);
+    assert.equal(await balance(vaultB), 50_000_000_000);
+    assert.equal(await balance(b.ownerShares), 1_000_000);
+
+    // 3) a later deposit pays the pro‐rata slice, and no more than max_amounts
+    await assert.rejects(
+      pg.program.methods
+        .depositInKind(new BN(500_000), [new BN(49_999_999), new BN(25_000_000_000)])
+        .accounts(shareAccounts)
+        .remainingAccounts(pairs)
+        .rpc(),
+      /SlippageExceeded/
+    );
+    await pg.program.methods
+      .depositInKind(new BN(500_000), [new BN(50_000_000), new BN(25_000_000_000)])
+      .accounts(shareAccounts)
+      .remainingAccounts(pairs)
+      .rpc();
+    assert.equal(await balance(vaultA), 150_000_000);
+    assert.equal(await balance(vaultB), 75_000_000_000);
+    assert.equal(await balance(b.ownerShares), 1_500_000);
+
+    // 4) redeeming every share empties the vaults back to the owner
+    await pg.program.methods
+      .redeemInKind(new BN(1_500_000), [new BN(150_000_000), new BN(75_000_000_000)])
+      .accounts(shareAccounts)
+      .remainingAccounts(pairs)
+      .rpc();
+    assert.equal(await balance(vaultA), 0);
+    assert.equal(await balance(vaultB), 0);
+    assert.equal(await balance(b.ownerShares), 0);
+    assert.equal(await balance(ownedA), 1_000_000_000);
+    assert.equal(await balance(ownedB), 1_000_000_000_000);
+    const shareMint = await splToken.getMint(pg.connection, b.shareMint);
+    assert.equal(Number(shareMint.supply), 0);
+
+    // 5) a donation left in an empty basket blocks reseeding until swept
+    await splToken.transfer(pg.connection, payer(), ownedA, vaultA, payer(), 1);
+    await assert.rejects(
+      pg.program.methods
+        .depositInKind(new BN(1_000_000), [new BN(100_000_000), new BN(50_000_000_000)])
+        .accounts(shareAccounts)
+        .remainingAccounts(pairs)
+        .rpc(),
+      /VaultNotEmpty/
+    );
+    const governance = pda(Buffer.from("governance"), b.basket.toBuffer());
+    const governanceA = await splToken.createAccount(
+      pg.connection, payer(), mintA, governance, web3.Keypair.generate()
+    );
+    await pg.program.methods
+      .sweepAssetVault()
+      .accounts({
+        basket:       b.basket,
+        mint:         mintA,
+        shareMint:    b.shareMint,
+        basketAuth:   b.basketAuth,
+        vault:        vaultA,
+        governance,
+        destination:  governanceA,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    assert.equal(await balance(vaultA), 0);
+    assert.equal(await balance(governanceA), 1);
+  });
+
+  // ─── Single‐asset shares ─────────────────────────────────────────────────
+
+  it("prices single‐asset deposits and redemptions at oracle NAV", async () => {
+    // 1) 50/50 basket, 5% weight band, 30/50 bps entry/exit fees, seeded in kind
+    const { mint: mintA, owned: ownedA } = await fundedMint(6, 1_000_000_000);
+    const { mint: mintB, owned: ownedB } = await fundedMint(9, 1_000_000_000_000);
+    const b = await initBasket(
+      [
+        { mint: mintA, weightBps: 5_000 },
+        { mint: mintB, weightBps: 5_000 },
+      ],
+      500,
+      { entryFeeBps: 30, exitFeeBps: 50 }
+    );
+    const a = await createVault(b.basket, mintA);
+    const c = await createVault(b.basket, mintB);
+    await pg.program.methods
+      .depositInKind(new BN(1_000_000), [new BN(100_000_000), new BN(50_000_000_000)])
+      .accounts({
+        owner:        pg.wallet.publicKey,
+        basket:       b.basket,
+        basketAuth:   b.basketAuth,
+        shareMint:    b.shareMint,
+        ownerShares:  b.ownerShares,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(writable(a.vault, ownedA, c.vault, ownedB))
+      .rpc();
+    const holdings = [
+      ...writable(a.vault),
+      { pubkey: a.priceFeed, isSigner: false, isWritable: false },
+      ...writable(c.vault),
+      { pubkey: c.priceFeed, isSigner: false, isWritable: false },
+    ];
+    const single = (mint: web3.PublicKey, ownerTokens: web3.PublicKey) => ({
+      owner:        pg.wallet.publicKey,
+      basket:       b.basket,
+      composition:  b.composition,
+      basketAuth:   b.basketAuth,
+      shareMint:    b.shareMint,
+      ownerShares:  b.ownerShares,
+      mint,
+      ownerTokens,
+      tokenProgram: splToken.TOKEN_PROGRAM_ID,
+    });
+
+    // 2) prices never posted count as stale
+    await assert.rejects(
+      pg.program.methods
+        .depositSingle(new BN(2_000_000), new BN(0))
+        .accounts(single(mintA, ownedA))
+        .remainingAccounts(holdings)
+        .rpc(),
+      /StalePrice/
+    );
+
+    // 3) A at 1.00 and B at 2.00 quote units: 100 A + 50 B, NAV 200
+    for (const [feed, price] of [[a.priceFeed, 1_000_000], [c.priceFeed, 2_000_000]] as const) {
+      await pg.program.methods
+        .updatePrice(new BN(price))
+        .accounts({
+          oracleAuthority: pg.wallet.publicKey,
+          basket:          b.basket,
+          priceFeed:       feed,
+        })
+        .rpc();
+    }
+    const feedB = await pg.program.account.priceFeed.fetch(c.priceFeed);
+    assert.ok(feedB.price.eqn(2_000_000));
+    assert.equal(feedB.decimals, 9);
+
+    // 4) 2 A is worth 2.00; after the 30 bps fee that buys 1.994/200 of the supply
+    await pg.program.methods
+      .depositSingle(new BN(2_000_000), new BN(9_970))
+      .accounts(single(mintA, ownedA))
+      .remainingAccounts(holdings)
+      .rpc();
+    assert.equal(await balance(b.ownerShares), 1_009_970);
+    assert.equal(await balance(a.vault), 102_000_000);
+
+    // 5) 50 A more would leave A at ~60% against a 50% ± 5% band
+    await assert.rejects(
+      pg.program.methods
+        .depositSingle(new BN(50_000_000), new BN(0))
+        .accounts(single(mintA, ownedA))
+        .remainingAccounts(holdings)
+        .rpc(),
+      /WeightDeviationExceeded/
+    );
+
+    // 6) redeeming those shares for B pays their NAV share less the 50 bps fee
+    const nav = new BN(202_000_000);
+    const valueOut = new BN(9_970).mul(nav).div(new BN(1_009_970));
+    const fee = valueOut.muln(50).divn(10_000);
+    const expectedB = valueOut.sub(fee).mul(new BN(1_000_000_000)).div(new BN(2_000_000));
+    const vaultBefore = await balance(c.vault);
+    await pg.program.methods
+      .redeemSingle(new BN(9_970), expectedB)
+      .accounts(single(mintB, ownedB))
+      .remainingAccounts(holdings)
+      .rpc();
+    assert.equal(await balance(b.ownerShares), 1_000_000);
+    assert.equal(vaultBefore - (await balance(c.vault)), expectedB.toNumber());
+    assert.equal(expectedB.toNumber(), 992_044_500);
+  });
+
+  // ─── Governance helpers ──────────────────────────────────────────────────
+
+  const WEEK = 7 * 86_400;
+  type Basket = Awaited<ReturnType<typeof initBasket>>;
+  type Staker = { owner: web3.Keypair; stakeAccount: web3.PublicKey; tokens: web3.PublicKey };
+
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+  // The cluster's `Clock::unix_timestamp`, which is what the program checks.
+  const chainTime = async () => {
+    const clock = await pg.connection.getAccountInfo(web3.SYSVAR_CLOCK_PUBKEY);
+    return Number(clock!.data.readBigInt64LE(32));
+  };
+  async function waitPast(ts: number) {
+    while ((await chainTime()) <= ts) await sleep(1_000);
+  }
+  const tagOf = (b: Basket, s: string) => pda(Buffer.from(s), b.basket.toBuffer());
+  const voteRecord = (proposal: web3.PublicKey, voter: web3.PublicKey) =>
+    pda(Buffer.from("vote"), proposal.toBuffer(), voter.toBuffer());
+  const delegatedRecord = (proposal: web3.PublicKey, voter: web3.PublicKey) =>
+    pda(Buffer.from("delegated_vote"), proposal.toBuffer(), voter.toBuffer());
+  const delegation = (b: Basket, delegator: web3.PublicKey) =>
+    pda(Buffer.from("delegation"), b.basket.toBuffer(), delegator.toBuffer());
+
+  // Stake account for `owner` holding `minted` REBAL, `locked` of it for 8 weeks.
+  async function staker(b: Basket, owner: web3.Keypair, minted: number, locked: number) {
+    const stakeAccount = pda(Buffer.from("stake"), b.basket.toBuffer(), owner.publicKey.toBuffer());
+    await pg.program.methods
+      .createStakeAccount()
+      .accounts({
+        owner:         owner.publicKey,
+        basket:        b.basket,
+        stakeAccount,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    const tokens = await splToken.createAccount(
+      pg.connection, payer(), b.rebalMint, owner.publicKey, web3.Keypair.generate()
+    );
+    if (minted > 0) {
+      await splToken.mintTo(pg.connection, payer(), b.rebalMint, tokens, payer(), minted);
+    }
+    if (locked > 0) {
+      await pg.program.methods
+        .lockRebal(new BN(locked), new BN((await chainTime()) + 8 * WEEK))
+        .accounts({
+          owner:        owner.publicKey,
+          basket:       b.basket,
+          votingSupply: tagOf(b, "voting_supply"),
+          stakeAccount,
+          ownerTokens:  tokens,
+          stakeVault:   tagOf(b, "stake_vault"),
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        })
+        .signers([owner])
+        .rpc();
+    }
+    return { owner, stakeAccount, tokens };
+  }
+
+  // Keypair with some SOL from the wallet for rent and fees.
+  async function fundedKeypair() {
+    const kp = web3.Keypair.generate();
+    await web3.sendAndConfirmTransaction(
+      pg.connection,
+      new web3.Transaction().add(
+        web3.SystemProgram.transfer({
+          fromPubkey: pg.wallet.publicKey,
+          toPubkey:   kp.publicKey,
+          lamports:   web3.LAMPORTS_PER_SOL / 10,
+        })
+      ),
+      [payer()]
+    );
+    return kp;
+  }
+
+  // Opens a proposal whose voting closes `votingSeconds` from now.
+  async function propose(b: Basket, proposer: Staker, action: object, votingSeconds: number) {
+    const { proposalCount } = await pg.program.account.basketConfig.fetch(b.basket);
+    const proposal = pda(
+      Buffer.from("proposal"), b.basket.toBuffer(), proposalCount.toArrayLike(Buffer, "le", 8)
+    );
+    await pg.program.methods
+      .createProposal(action, new BN((await chainTime()) + votingSeconds))
+      .accounts({
+        proposer:       proposer.owner.publicKey,
+        basket:         b.basket,
+        proposal,
+        votingSupply:   tagOf(b, "voting_supply"),
+        proposerStake:  proposer.stakeAccount,
+        proposerTokens: proposer.tokens,
+        depositVault:   tagOf(b, "deposit_vault"),
+        tokenProgram:   splToken.TOKEN_PROGRAM_ID,
+        systemProgram:  web3.SystemProgram.programId,
+        clock:          web3.SYSVAR_CLOCK_PUBKEY,
+      })
+      .signers([proposer.owner])
+      .rpc();
+    return proposal;
+  }
+
+  function finalize(b: Basket, proposal: web3.PublicKey, proposer: Staker) {
+    return pg.program.methods
+      .finalizeProposal()
+      .accounts({
+        finalizer:      pg.wallet.publicKey,
+        basket:         b.basket,
+        proposal,
+        proposerTokens: proposer.tokens,
+        escrowAuth:     tagOf(b, "escrow_auth"),
+        depositVault:   tagOf(b, "deposit_vault"),
+        treasury:       b.treasury,
+        rebalMint:      b.rebalMint,
+        tokenProgram:   splToken.TOKEN_PROGRAM_ID,
+        clock:          web3.SYSVAR_CLOCK_PUBKEY,
+      })
+      .rpc();
+  }
+
+  function execute(b: Basket, proposal: web3.PublicKey) {
+    return pg.program.methods
+      .executeProposal()
+      .accounts({
+        executor:    pg.wallet.publicKey,
+        basket:      b.basket,
+        proposal,
+        composition: b.composition,
+        governance:  tagOf(b, "governance"),
+      })
+      .rpc();
+  }
+
+  // ─── Governance ──────────────────────────────────────────────────────────
+
+  it("queues, times out and executes proposals against the current config", async () => {
+    // 1) 1 s minimum voting period, 10 s timelock, 100 REBAL deposits forfeited to the treasury
+    const { mint } = await fundedMint(6, 1);
+    const b = await initBasket([{ mint, weightBps: 10_000 }], 500, {
+      executionDelay:  10,
+      proposalDeposit: 100_000_000,
+      minVotingPeriod: 1,
+      depositForfeit:  { treasury: {} },
+    });
+    const proposer = await staker(b, payer(), 1_000_000_000, 500_000_000);
+
+    // 2) three proposals opened against the same config version
+    const raise = await propose(b, proposer, { threshold: { 0: new BN(700) } }, 20);
+    const rival = await propose(b, proposer, { threshold: { 0: new BN(900) } }, 20);
+    const ignored = await propose(b, proposer, { strategy: { 0: 1 } }, 20);
+    assert.equal(await balance(proposer.tokens), 200_000_000);
+
+    // 3) the only staker backs the first two with its whole snapshot power
+    for (const proposal of [raise, rival]) {
+      await pg.program.methods
+        .castVote({ yes: {} })
+        .accounts({
+          staker:          pg.wallet.publicKey,
+          basket:          b.basket,
+          proposal,
+          stakeAccount:    proposer.stakeAccount,
+          delegation:      delegation(b, pg.wallet.publicKey),
+          delegatedRecord: delegatedRecord(proposal, pg.wallet.publicKey),
+          voteRecord:      voteRecord(proposal, pg.wallet.publicKey),
+          systemProgram:   web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
+    const cast = await pg.program.account.voteRecord.fetch(voteRecord(raise, pg.wallet.publicKey));
+    let p = await pg.program.account.proposal.fetch(raise);
+    assert.ok(cast.power.gtn(0));
+    assert.ok(p.yesPower.eq(cast.power));
+    assert.ok(p.participation.mul(new BN(100)).gte(p.snapshotSupply.muln(p.quorumPercentage)));
+    await assert.rejects(finalize(b, raise, proposer), /ProposalStillActive/);
+
+    // 4) once voting closes the backed proposal queues and gets its deposit back…
+    await waitPast((await pg.program.account.proposal.fetch(ignored)).expiration.toNumber());
+    await finalize(b, raise, proposer);
+    p = await pg.program.account.proposal.fetch(raise);
+    assert.deepEqual(p.state, { queued: {} });
+    assert.ok(p.deposit.eqn(0));
+    assert.equal(await balance(proposer.tokens), 300_000_000);
+    await assert.rejects(execute(b, raise), /TimelockActive/);
+
+    // …while the one nobody voted on expires and forfeits its deposit
+    const treasuryBefore = await balance(b.treasury);
+    await finalize(b, ignored, proposer);
+    const expired = await pg.program.account.proposal.fetch(ignored);
+    assert.deepEqual(expired.state, { expired: {} });
+    assert.equal((await balance(b.treasury)) - treasuryBefore, 100_000_000);
+    assert.equal(await balance(proposer.tokens), 300_000_000);
+
+    // 5) after the timelock execution applies the change and bumps the nonce
+    await waitPast(p.eta.toNumber() - 1);
+    await execute(b, raise);
+    assert.deepEqual((await pg.program.account.proposal.fetch(raise)).state, { executed: {} });
+    let cfg = await pg.program.account.basketConfig.fetch(b.basket);
+    assert.ok(cfg.threshold.eqn(700));
+    assert.ok(cfg.proposalNonce.eqn(1));
+
+    // 6) the rival passed too, but was opened against the old config
+    await finalize(b, rival, proposer);
+    p = await pg.program.account.proposal.fetch(rival);
+    assert.deepEqual(p.state, { queued: {} });
+    await waitPast(p.eta.toNumber() - 1);
+    await assert.rejects(execute(b, rival), /StaleProposal/);
+    cfg = await pg.program.account.basketConfig.fetch(b.basket);
+    assert.ok(cfg.threshold.eqn(700));
+  });
+
+  // ─── Delegation ──────────────────────────────────────────────────────────
+
+  it("caps delegated votes at the delegator's power and releases them on retract", async () => {
+    // 1) the wallet locks 400 REBAL and delegates 100 of it to a stakeless delegate
+    const { mint } = await fundedMint(6, 1);
+    const b = await initBasket([{ mint, weightBps: 10_000 }], 500, { minVotingPeriod: 1 });
+    const delegator = await staker(b, payer(), 400_000_000, 400_000_000);
+    const delegate = await staker(b, await fundedKeypair(), 0, 0);
+    await pg.program.methods
+      .delegateVotes(delegate.owner.publicKey, new BN(100_000_000))
+      .accounts({
+        delegator:     pg.wallet.publicKey,
+        basket:        b.basket,
+        delegation:    delegation(b, pg.wallet.publicKey),
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    const first = await propose(b, delegator, { threshold: { 0: new BN(700) } }, 60);
+    const second = await propose(b, delegator, { threshold: { 0: new BN(900) } }, 60);
+
+    const vote = (proposal: web3.PublicKey, voter: Staker, choice: object, delegators: Staker[] = []) =>
+      pg.program.methods
+        .castVote(choice)
+        .accounts({
+          staker:          voter.owner.publicKey,
+          basket:          b.basket,
+          proposal,
+          stakeAccount:    voter.stakeAccount,
+          delegation:      delegation(b, voter.owner.publicKey),
+          delegatedRecord: delegatedRecord(proposal, voter.owner.publicKey),
+          voteRecord:      voteRecord(proposal, voter.owner.publicKey),
+          systemProgram:   web3.SystemProgram.programId,
+        })
+        .remainingAccounts(
+          delegators.flatMap((d) => [
+            { pubkey: delegation(b, d.owner.publicKey), isSigner: false, isWritable: false },
+            ...writable(d.stakeAccount, delegatedRecord(proposal, d.owner.publicKey)),
+            { pubkey: voteRecord(proposal, d.owner.publicKey), isSigner: false, isWritable: false },
+          ])
+        )
+        .signers([voter.owner])
+        .rpc();
+    const retract = (proposal: web3.PublicKey, voter: Staker, delegators: Staker[] = []) =>
+      pg.program.methods
+        .retractVote()
+        .accounts({
+          staker:       voter.owner.publicKey,
+          basket:       b.basket,
+          proposal,
+          voteRecord:   voteRecord(proposal, voter.owner.publicKey),
+          stakeAccount: voter.stakeAccount,
+        })
+        .remainingAccounts(
+          delegators.flatMap((d) =>
+            writable(delegatedRecord(proposal, d.owner.publicKey), d.stakeAccount, voter.owner.publicKey)
+          )
+        )
+        .signers([voter.owner])
+        .rpc();
+
+    // The delegator's whole snapshot power: amount * remaining / MAX_LOCK_SECONDS.
+    const MAX_LOCK_SECONDS = 4 * 365 * 86_400;
+    const stake = await pg.program.account.stakeAccount.fetch(delegator.stakeAccount);
+    const ownPower = async (proposal: web3.PublicKey) => {
+      const { snapshotTs } = await pg.program.account.proposal.fetch(proposal);
+      return stake.amount.mul(stake.lockEnd.sub(snapshotTs)).div(new BN(MAX_LOCK_SECONDS));
+    };
+    const votedPower = async (proposal: web3.PublicKey) => {
+      const direct = await pg.program.account.voteRecord.fetch(voteRecord(proposal, pg.wallet.publicKey));
+      const counted = await pg.program.account.voteRecord.fetch(delegatedRecord(proposal, pg.wallet.publicKey));
+      assert.ok(direct.power.gtn(0) && counted.power.gtn(0));
+      return direct.power.add(counted.power);
+    };
+
+    // 2) delegate first: the delegate counts the delegated 100, the delegator the rest
+    await vote(first, delegate, { yes: {} }, [delegator]);
+    await vote(first, delegator, { no: {} });
+    assert.ok((await votedPower(first)).lte(await ownPower(first)));
+    const delegateVote = await pg.program.account.voteRecord.fetch(voteRecord(first, delegate.owner.publicKey));
+    assert.equal(delegateVote.delegators, 1);
+    assert.ok(delegateVote.power.eq(delegateVote.delegatedPower));
+
+    // 3) delegator first: the delegate only gets what the direct vote left over
+    await vote(second, delegator, { yes: {} });
+    await vote(second, delegate, { no: {} }, [delegator]);
+    assert.ok((await votedPower(second)).lte(await ownPower(second)));
+    assert.equal(
+      (await pg.program.account.stakeAccount.fetch(delegator.stakeAccount)).activeVotes,
+      4
+    );
+
+    // 4) a delegate's retract must release every delegator it counted…
+    await assert.rejects(retract(first, delegate), /BadRefundAccounts/);
+    await retract(first, delegate, [delegator]);
+    assert.equal(await pg.connection.getAccountInfo(delegatedRecord(first, pg.wallet.publicKey)), null);
+    assert.equal(
+      (await pg.program.account.stakeAccount.fetch(delegator.stakeAccount)).activeVotes,
+      3
+    );
+    assert.equal((await pg.program.account.proposal.fetch(first)).openVotes, 1);
+
+    // …so once both votes on both proposals are retracted the stake is free again
+    await retract(first, delegator);
+    await retract(second, delegate, [delegator]);
+    await retract(second, delegator);
+    const released = await pg.program.account.stakeAccount.fetch(delegator.stakeAccount);
+    assert.equal(released.activeVotes, 0);
   });
 });